@@ -1,20 +1,97 @@
-use clap::Parser;
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+
+use clap::{Parser, Subcommand, ValueEnum};
 use crate::error::*;
+use crate::error::diagnostic::{Diagnostics, SourceMap};
+use crate::error::lint::{LintLevel, LintLevels};
+use crate::generating::Generator;
+use crate::generating::writer::Writer;
+use crate::interpreting::{self, Interpreter};
+use crate::interpreting::value::Value;
+use crate::parsing::Parser as SourceParser;
+use crate::parsing::ast::ASTNode;
+use crate::scanning::{Scanner, STDIN_FILENAME};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ColorChoice {
+	Auto,
+	Always,
+	Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+	Human,
+	Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+	// Check a file and run it with the interpreter instead of compiling it
+	Run {
+		// File to run; '-' reads from stdin
+		file: String,
+	},
+}
 
 #[derive(Debug, Parser)]
-#[command(author, version)]
+#[command(author, version, subcommand_negates_reqs = true)]
 pub struct Args {
-	// Input files to be compiled
+	#[command(subcommand)]
+	command: Option<Command>,
+
+	// Input files to be compiled; '-' reads from stdin
 	#[arg(required=true)]
 	input_files: Vec<String>,
 
 	// Enable debug mode
 	#[arg(short, long)]
 	debug: bool,
+
+	// Check array indexes at runtime, aborting if one is out of bounds; on by default with --debug
+	#[arg(long, overrides_with = "no_bounds_checks")]
+	bounds_checks: bool,
+
+	// Leave out the bounds checks --debug turns on
+	#[arg(long, overrides_with = "bounds_checks")]
+	no_bounds_checks: bool,
+
+	// Whether diagnostics are colored
+	#[arg(long, value_enum, default_value_t = ColorChoice::Auto, global = true)]
+	color: ColorChoice,
+
+	// How diagnostics are printed; json prints one object per line
+	#[arg(long, value_enum, default_value_t = ErrorFormat::Human, global = true)]
+	error_format: ErrorFormat,
+
+	// Stop reporting errors after this many; 0 reports all of them
+	#[arg(long, default_value_t = 20, global = true)]
+	error_limit: usize,
+
+	// Lints to warn about; '-W error' is the same as --deny-warnings
+	#[arg(short = 'W', value_name = "LINT", global = true)]
+	warn: Vec<String>,
+
+	// Lints to ignore; 'warnings' covers every lint
+	#[arg(short = 'A', value_name = "LINT", global = true)]
+	allow: Vec<String>,
+
+	// Lints to report as errors; 'warnings' covers every lint
+	#[arg(short = 'D', value_name = "LINT", global = true)]
+	deny: Vec<String>,
+
+	// Report every warning as an error
+	#[arg(long, global = true)]
+	deny_warnings: bool,
 }
 
 impl Args {
 	// Accessors
+	pub fn command(&self) -> Option<&Command> {
+		self.command.as_ref()
+	}
+
 	pub fn input_files(&self) -> &Vec<String> {
 		&self.input_files
 	}
@@ -22,24 +99,164 @@ impl Args {
 	pub fn debug(&self) -> bool {
 		self.debug
 	}
+
+	// Whether generated code checks array indexes; the last of --bounds-checks and --no-bounds-checks wins
+	pub fn bounds_checks(&self) -> bool {
+		self.bounds_checks || (self.debug && !self.no_bounds_checks)
+	}
+
+	pub fn error_format(&self) -> ErrorFormat {
+		self.error_format
+	}
+
+	pub fn error_limit(&self) -> usize {
+		self.error_limit
+	}
+
+	// Level of each lint; allows override warns and denies override both, regardless of order
+	pub fn lint_levels(&self) -> Result<LintLevels> {
+		let mut levels = LintLevels::new();
+		if self.deny_warnings {
+			levels.deny_warnings();
+		}
+
+		for name in self.warn.iter() {
+			if name == "error" {
+				levels.deny_warnings();
+			} else {
+				levels.set(name, LintLevel::Warn)?;
+			}
+		}
+		for name in self.allow.iter() {
+			levels.set(name, LintLevel::Allow)?;
+		}
+		for name in self.deny.iter() {
+			levels.set(name, LintLevel::Deny)?;
+		}
+
+		Ok(levels)
+	}
+
+	// Whether diagnostics should be colored; auto colors them only when printing to a terminal
+	pub fn use_color(&self) -> bool {
+		match self.color {
+			ColorChoice::Auto => std::io::stderr().is_terminal(),
+			ColorChoice::Always => true,
+			ColorChoice::Never => false,
+		}
+	}
 }
 
 pub fn parse_args() -> Args {
 	Args::parse()
 }
 
-pub fn compile(args: &Args) -> Result<()> {
-	for (_, filename) in args.input_files().iter().enumerate() {
+// Compile every input file, reporting problems to diagnostics; the source of each file is kept in sources to render them
+pub fn compile(args: &Args, diagnostics: &mut Diagnostics, sources: &mut SourceMap) {
+	let lint_levels = match args.lint_levels() {
+		Ok(lint_levels) => lint_levels,
+		Err(error) => {
+			diagnostics.error(&error);
+			return;
+		}
+	};
+
+	for filename in args.input_files().iter() {
 		if args.debug() {
 			println!("Compiling {}.", filename);
 		}
 
-		let scanner = crate::scanning::Scanner::open_file(filename.to_owned())?;
-		let mut parser = crate::parsing::Parser::new(scanner)?;
+		if let Err(error) = compile_file(filename, &lint_levels, args.bounds_checks(), diagnostics, sources) {
+			diagnostics.error(&error);
+		}
+	}
+}
+
+// Check and interpret a file, reporting problems to diagnostics; returns what main returned if it ran
+pub fn run(args: &Args, filename: &str, diagnostics: &mut Diagnostics, sources: &mut SourceMap) -> Option<Value> {
+	let lint_levels = match args.lint_levels() {
+		Ok(lint_levels) => lint_levels,
+		Err(error) => {
+			diagnostics.error(&error);
+			return None;
+		}
+	};
 
-		let mut generator = crate::generating::Generator::from_filename(filename.to_owned() + ".ll")?;
-		generator.generate(&mut parser)?;
+	match run_file(filename, &lint_levels, diagnostics, sources) {
+		Ok(value) => value,
+		Err(error) => {
+			diagnostics.error(&error);
+			None
+		}
+	}
+}
+
+// Compile a single file; code is only generated if the whole file parsed, and only kept if it is free of errors and denied lints
+pub fn compile_file(filename: &str, lint_levels: &LintLevels, bounds_checks: bool, diagnostics: &mut Diagnostics, sources: &mut SourceMap) -> Result<()> {
+	let Some(program) = parse_file(filename, diagnostics, sources)? else {
+		return Ok(());
+	};
+
+	// Code read from stdin has no file to put its output next to
+	let output_filename = if filename == STDIN_FILENAME { String::from("stdin.ll") } else { filename.to_owned() + ".ll" };
+	let mut generator = Generator::from_filename(output_filename.clone())?;
+	generator.set_bounds_checks(bounds_checks);
+	generator.generate(&program)?;
+
+	if report_generated(&generator, lint_levels, diagnostics) {
+		std::fs::remove_file(&output_filename).map_err(|cause| Error::FileWriteError { cause })?;
 	}
 
 	Ok(())
+}
+
+// Check a file the same way it is compiled, then interpret it.
+// Returns what main returned, or None if the file had errors and wasn't run.
+pub fn run_file(filename: &str, lint_levels: &LintLevels, diagnostics: &mut Diagnostics, sources: &mut SourceMap) -> Result<Option<Value>> {
+	let Some(program) = parse_file(filename, diagnostics, sources)? else {
+		return Ok(None);
+	};
+
+	// The generated code is only needed for the errors found while generating it
+	let mut generator = Generator::new(Writer::new(filename.to_owned(), io::sink()));
+	generator.generate(&program)?;
+	if report_generated(&generator, lint_levels, diagnostics) {
+		return Ok(None);
+	}
+
+	// Each interpreted call takes several native frames, more than the main thread has room for
+	thread::scope(|scope| {
+		thread::Builder::new()
+			.stack_size(interpreting::STACK_SIZE)
+			.spawn_scoped(scope, || Interpreter::new(io::stdout()).run(&program))
+			.expect("failed to start interpreter thread")
+			.join()
+			.expect("interpreter thread panicked")
+	}).map(Some)
+}
+
+// Parse a file, keeping its source to render diagnostics; the program is only returned if there were no syntax errors
+fn parse_file(filename: &str, diagnostics: &mut Diagnostics, sources: &mut SourceMap) -> Result<Option<Vec<ASTNode>>> {
+	let scanner = Scanner::open_file(filename.to_owned())?;
+	let mut parser = SourceParser::new(scanner)?;
+	let program = parser.parse_program();
+	sources.add(parser.scanner().filename().to_owned(), parser.scanner().source().to_owned());
+
+	if !parser.errors().is_empty() {
+		diagnostics.errors(parser.errors());
+		return Ok(None);
+	}
+
+	Ok(Some(program))
+}
+
+// Report the errors and warnings found by the generator; returns whether any of them stop its output from being used
+fn report_generated<W: Write>(generator: &Generator<W>, lint_levels: &LintLevels, diagnostics: &mut Diagnostics) -> bool {
+	let mut failed = !generator.errors().is_empty();
+	diagnostics.errors(generator.errors());
+	for warning in generator.warnings() {
+		failed |= diagnostics.warning(warning, lint_levels);
+	}
+
+	failed
 }
\ No newline at end of file
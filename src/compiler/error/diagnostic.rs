@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+use crate::scanning::span::Span;
+use super::Error;
+use super::lint::{LintLevel, LintLevels, Warning};
+
+const TAB_WIDTH: usize = 4;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const GREEN: &str = "\x1b[1;32m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+// Extra information attached to a diagnostic, optionally pointing at related source
+#[derive(Debug, Clone)]
+pub struct Note {
+	span: Option<Span>,
+	message: String,
+}
+
+impl Note {
+	pub fn new(span: Option<Span>, message: String) -> Self {
+		Self {
+			span,
+			message,
+		}
+	}
+
+	pub fn span(&self) -> Option<&Span> {
+		self.span.as_ref()
+	}
+
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Severity::Error => write!(f, "error"),
+			Severity::Warning => write!(f, "warning"),
+		}
+	}
+}
+
+// A message about the compiled program, ready to be rendered
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+	severity: Severity,
+	code: Option<String>,
+	message: String,
+	span: Option<Span>,
+	notes: Vec<Note>,
+}
+
+impl Diagnostic {
+	pub fn new(severity: Severity, message: String, span: Option<Span>, notes: Vec<Note>) -> Self {
+		Self {
+			severity,
+			code: None,
+			message,
+			span,
+			notes,
+		}
+	}
+
+	// Set the code of the error or lint behind the diagnostic
+	pub fn with_code(mut self, code: &str) -> Self {
+		self.code = Some(code.to_owned());
+		self
+	}
+
+	pub fn severity(&self) -> Severity {
+		self.severity
+	}
+
+	pub fn code(&self) -> Option<&str> {
+		self.code.as_deref()
+	}
+
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+
+	pub fn span(&self) -> Option<&Span> {
+		self.span.as_ref()
+	}
+
+	pub fn notes(&self) -> &[Note] {
+		&self.notes
+	}
+
+	// Serialize as a single line JSON object:
+	// {"code", "severity", "message", "spans": [{"file", "line", "column", "start", "end"}], "notes": [{"message", "spans"}]}
+	pub fn to_json(&self) -> String {
+		let code = self.code.as_deref().map_or(String::from("null"), json_string);
+		let notes: Vec<String> = self.notes.iter()
+			.map(|note| format!("{{\"message\":{},\"spans\":{}}}", json_string(note.message()), json_spans(note.span())))
+			.collect();
+
+		format!("{{\"code\":{code},\"severity\":{},\"message\":{},\"spans\":{},\"notes\":[{}]}}",
+			json_string(&self.severity.to_string()), json_string(&self.message), json_spans(self.span()), notes.join(","))
+	}
+}
+
+impl From<&Error> for Diagnostic {
+	fn from(error: &Error) -> Self {
+		Self::new(Severity::Error, error.root().to_string(), error.span().cloned(), error.notes().to_vec()).with_code(error.code())
+	}
+}
+
+// Quote and escape text as a JSON string
+fn json_string(text: &str) -> String {
+	let mut out = String::from("\"");
+	for c in text.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+
+	out
+}
+
+// JSON array holding the span, if there is one
+fn json_spans(span: Option<&Span>) -> String {
+	match span {
+		Some(span) => format!("[{{\"file\":{},\"line\":{},\"column\":{},\"start\":{},\"end\":{}}}]",
+			json_string(span.file()), span.line(), span.column(), span.start(), span.end()),
+		None => String::from("[]"),
+	}
+}
+
+// Collects the diagnostics of a compilation; only the first error_limit errors are kept (0 keeps all)
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+	diagnostics: Vec<Diagnostic>,
+	error_count: usize,
+	warning_count: usize,
+	error_limit: usize,
+}
+
+impl Diagnostics {
+	pub fn new(error_limit: usize) -> Self {
+		Self {
+			diagnostics: Vec::new(),
+			error_count: 0,
+			warning_count: 0,
+			error_limit,
+		}
+	}
+
+	pub fn push(&mut self, diagnostic: Diagnostic) {
+		match diagnostic.severity() {
+			Severity::Error => {
+				self.error_count += 1;
+				if self.error_limit == 0 || self.error_count <= self.error_limit {
+					self.diagnostics.push(diagnostic);
+				}
+			},
+			Severity::Warning => {
+				self.warning_count += 1;
+				self.diagnostics.push(diagnostic);
+			},
+		}
+	}
+
+	pub fn error(&mut self, error: &Error) {
+		self.push(Diagnostic::from(error));
+	}
+
+	// Report a warning at the level its lint is set to; returns whether it was reported as an error
+	pub fn warning(&mut self, warning: &Warning, levels: &LintLevels) -> bool {
+		let severity = match levels.level(warning.lint()) {
+			LintLevel::Allow => return false,
+			LintLevel::Warn => Severity::Warning,
+			LintLevel::Deny => Severity::Error,
+		};
+
+		let mut notes = warning.notes().to_vec();
+		notes.push(Note::new(None, levels.reason(warning.lint())));
+		self.push(Diagnostic::new(severity, warning.message().to_owned(), Some(warning.span().clone()), notes).with_code(warning.lint().name()));
+
+		severity == Severity::Error
+	}
+
+	pub fn errors(&mut self, errors: &[Error]) {
+		for error in errors {
+			self.error(error);
+		}
+	}
+
+	pub fn has_errors(&self) -> bool {
+		self.error_count > 0
+	}
+
+	pub fn error_count(&self) -> usize {
+		self.error_count
+	}
+
+	pub fn warning_count(&self) -> usize {
+		self.warning_count
+	}
+
+	// Number of errors that were dropped for going over the limit
+	pub fn suppressed_count(&self) -> usize {
+		self.error_count + self.warning_count - self.diagnostics.len()
+	}
+
+	pub fn diagnostics(&self) -> &[Diagnostic] {
+		&self.diagnostics
+	}
+}
+
+// Source text of compiled files; files that weren't added are read from disk when first needed
+#[derive(Debug, Default)]
+pub struct SourceMap {
+	files: HashMap<String, String>,
+}
+
+impl SourceMap {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn add(&mut self, filename: String, source: String) {
+		self.files.insert(filename, source);
+	}
+
+	pub fn source(&mut self, filename: &str) -> Option<&str> {
+		if !self.files.contains_key(filename) {
+			let source = fs::read_to_string(filename).ok()?;
+			self.files.insert(filename.to_owned(), source);
+		}
+
+		self.files.get(filename).map(|source| source.as_str())
+	}
+}
+
+// Renders diagnostics rustc-style: a header, the offending source line and carets under the span
+#[derive(Debug)]
+pub struct Renderer {
+	color: bool,
+}
+
+impl Renderer {
+	pub fn new(color: bool) -> Self {
+		Self {
+			color,
+		}
+	}
+
+	pub fn render(&self, diagnostic: &Diagnostic, sources: &mut SourceMap) -> String {
+		let mut out = String::new();
+
+		let style = match diagnostic.severity() {
+			Severity::Error => RED,
+			Severity::Warning => YELLOW,
+		};
+
+		self.render_header(&mut out, &diagnostic.severity().to_string(), style, diagnostic.message());
+		if let Some(span) = diagnostic.span() {
+			self.render_snippet(&mut out, span, style, sources);
+		}
+
+		for note in diagnostic.notes() {
+			self.render_header(&mut out, "note", GREEN, note.message());
+			if let Some(span) = note.span() {
+				self.render_snippet(&mut out, span, GREEN, sources);
+			}
+		}
+
+		out.push('\n');
+		out
+	}
+
+	// Write 'level: message'
+	fn render_header(&self, out: &mut String, level: &str, level_style: &str, message: &str) {
+		out.push_str(&format!("{}{}\n", self.paint(&format!("{level}:"), level_style), self.paint(&format!(" {message}"), BOLD)));
+	}
+
+	// Write the location of the span followed by its first line with the span underlined
+	fn render_snippet(&self, out: &mut String, span: &Span, caret_style: &str, sources: &mut SourceMap) {
+		let line_number = span.line().to_string();
+		let gutter = " ".repeat(line_number.len());
+		out.push_str(&format!("{gutter}{} {span}\n", self.paint("-->", BLUE)));
+
+		let Some(source) = sources.source(span.file()) else {
+			return;
+		};
+		if span.start() > source.len() || !source.is_char_boundary(span.start()) {
+			return;
+		}
+
+		let line_start = source[..span.start()].rfind('\n').map_or(0, |i| i + 1);
+		let line_end = source[span.start()..].find('\n').map_or(source.len(), |i| span.start() + i);
+		let line = source[line_start..line_end].trim_end_matches('\r');
+
+		// Spans running past the end of the line are only underlined up to it
+		let prefix = &source[line_start..span.start()];
+		let underlined = &source[span.start()..span.end().clamp(span.start(), line_start + line.len())];
+		let caret_offset = Self::display_width(prefix);
+		let caret_count = Self::display_width(underlined).max(1);
+
+		let bar = self.paint("|", BLUE);
+		out.push_str(&format!("{gutter} {bar}\n"));
+		out.push_str(&format!("{} {bar} {}\n", self.paint(&line_number, BLUE), line.replace('\t', &" ".repeat(TAB_WIDTH))));
+		out.push_str(&format!("{gutter} {bar} {}{}\n", " ".repeat(caret_offset), self.paint(&"^".repeat(caret_count), caret_style)));
+	}
+
+	// Width of text once tabs are expanded
+	fn display_width(text: &str) -> usize {
+		text.chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+	}
+
+	fn paint(&self, text: &str, style: &str) -> String {
+		if self.color {
+			format!("{style}{text}{RESET}")
+		} else {
+			text.to_owned()
+		}
+	}
+}
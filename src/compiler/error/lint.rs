@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::scanning::span::Span;
+use super::{Error, Result};
+use super::diagnostic::Note;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+	UnusedVariables,
+	UnusedParameters,
+	UnusedFunctions,
+	UnreachableCode,
+	ImplicitInt,
+}
+
+pub const LINTS: &[(&str, Lint)] = &[
+	("unused_variables", Lint::UnusedVariables),
+	("unused_parameters", Lint::UnusedParameters),
+	("unused_functions", Lint::UnusedFunctions),
+	("unreachable_code", Lint::UnreachableCode),
+	("implicit_int", Lint::ImplicitInt),
+];
+
+// Name of the group that covers every lint
+pub const WARNINGS_GROUP: &str = "warnings";
+
+impl Lint {
+	pub fn name(&self) -> &'static str {
+		LINTS.iter().find(|lint| lint.1 == *self).map(|lint| lint.0).unwrap()
+	}
+
+	// Find a lint by name; '-' and '_' are interchangeable
+	pub fn from_name(name: &str) -> Option<Lint> {
+		let name = name.replace('-', "_");
+		LINTS.iter().find(|lint| lint.0 == name).map(|lint| lint.1)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+	Allow,
+	Warn,
+	Deny,
+}
+
+impl LintLevel {
+	pub fn flag(&self) -> &'static str {
+		match self {
+			LintLevel::Allow => "-A",
+			LintLevel::Warn => "-W",
+			LintLevel::Deny => "-D",
+		}
+	}
+}
+
+// Level each lint is reported at, along with the name it was set through; every lint warns unless told otherwise
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+	levels: HashMap<Lint, (LintLevel, String)>,
+	deny_warnings: bool,
+}
+
+impl LintLevels {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	// Set the level of the named lint, or of every lint for the 'warnings' group
+	pub fn set(&mut self, name: &str, level: LintLevel) -> Result<()> {
+		if name == WARNINGS_GROUP {
+			for lint in LINTS.iter() {
+				self.levels.insert(lint.1, (level, name.to_owned()));
+			}
+
+			return Ok(());
+		}
+
+		match Lint::from_name(name) {
+			Some(lint) => {
+				self.levels.insert(lint, (level, name.to_owned()));
+				Ok(())
+			},
+			None => Err(Error::UnknownLint { name: name.to_owned() }),
+		}
+	}
+
+	// Report every warning as an error
+	pub fn deny_warnings(&mut self) {
+		self.deny_warnings = true;
+	}
+
+	pub fn level(&self, lint: Lint) -> LintLevel {
+		match self.levels.get(&lint).map_or(LintLevel::Warn, |level| level.0) {
+			LintLevel::Warn if self.deny_warnings => LintLevel::Deny,
+			level => level,
+		}
+	}
+
+	// Explain why a lint is reported at its level
+	pub fn reason(&self, lint: Lint) -> String {
+		match self.levels.get(&lint) {
+			Some((LintLevel::Warn, _)) | None if self.deny_warnings => String::from("'--deny-warnings' turns warnings into errors"),
+			Some((level, name)) => format!("'{} {}' was given", level.flag(), name),
+			None => format!("'-W {}' is on by default", lint.name()),
+		}
+	}
+}
+
+// A problem that doesn't stop compilation unless its lint is denied
+#[derive(Debug, Clone)]
+pub struct Warning {
+	lint: Lint,
+	message: String,
+	span: Span,
+	notes: Vec<Note>,
+}
+
+impl Warning {
+	pub fn new(lint: Lint, message: String, span: Span) -> Self {
+		Self {
+			lint,
+			message,
+			span,
+			notes: Vec::new(),
+		}
+	}
+
+	pub fn with_note(mut self, span: &Span, message: &str) -> Self {
+		self.notes.push(Note::new(Some(span.clone()), message.to_owned()));
+		self
+	}
+
+	pub fn lint(&self) -> Lint {
+		self.lint
+	}
+
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+
+	pub fn span(&self) -> &Span {
+		&self.span
+	}
+
+	pub fn notes(&self) -> &[Note] {
+		&self.notes
+	}
+}
\ No newline at end of file
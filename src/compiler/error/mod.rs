@@ -1,11 +1,19 @@
+pub mod diagnostic;
+pub mod lint;
+
 use std::{fmt, result};
+use std::num::IntErrorKind;
+
+use diagnostic::Note;
 
 use crate::parsing::ast::Type;
+use crate::scanning::span::Span;
 use crate::scanning::token::{Identifier, Token};
 use crate::generating::llvm::{FunctionSignature, LLVMValue, RegisterFormat};
 
 pub type Result<T> = result::Result<T, Error>;
 
+#[derive(Debug)]
 pub enum Error {
 	FileOpenError { cause: std::io::Error },
 	FileReadError { cause: std::io::Error },
@@ -20,7 +28,7 @@ pub enum Error {
 	LiteralExpected { received: Token },
 	UnexpectedEOF { expected: Token },
 	UnexpectedLLVMValue { expected: LLVMValue, received: LLVMValue },
-	StringParseError { cause: std::num::ParseIntError },
+	StringParseError { literal: String, cause: std::num::ParseIntError },
 	SymbolUndefined { name: String },
 	SymbolDeclared { name: String },
 	StatementExpected,
@@ -34,7 +42,156 @@ pub enum Error {
 	UnexpectedFormat { expected: RegisterFormat, received: RegisterFormat },
 	BadConversion { from: RegisterFormat, to: RegisterFormat },
 	InvalidDereference { received: RegisterFormat },
-	ExpectedLValue
+	ExpectedLValue,
+	UnknownLint { name: String },
+	CompileFailed { errors: Vec<Error> },
+	DivisionByZero,
+	InvalidPointer,
+	MissingReturn { name: String },
+	StackOverflow { limit: usize },
+	UnterminatedComment,
+	InvalidUnaryOperand { operator: Token, received: RegisterFormat },
+	OperandMismatch { left: RegisterFormat, right: RegisterFormat },
+	LiteralOutOfRange { value: i128, format: RegisterFormat },
+	ConstantOverflow { left: i128, operator: Token, right: i128, format: RegisterFormat },
+	FloatParseError { literal: String, cause: std::num::ParseFloatError },
+	InvalidEscape { sequence: String },
+	InvalidCharLiteral { literal: String },
+	UnterminatedLiteral { literal: String },
+	ReadOnlyWrite,
+	InvalidFormatString { format: String },
+	FormatArgumentMismatch { expected: usize, received: usize },
+	NotIndexable { received: RegisterFormat },
+	IndexNotInteger { received: RegisterFormat },
+	Unprintable { received: RegisterFormat },
+	InvalidArrayLength,
+	IndexOutOfBounds { len: usize, index: i128 },
+	DuplicateField { name: String, field: String },
+	UnknownField { name: String, field: String },
+	MissingFields { name: String, fields: Vec<String> },
+	NotAStruct { received: RegisterFormat },
+	RecursiveStruct { name: String },
+	DuplicateVariant { name: String, variant: String },
+	UnknownVariant { name: String, variant: String },
+	DiscriminantOverflow { name: String, variant: String, value: i128 },
+	Located { span: Span, error: Box<Error> },
+	Noted { error: Box<Error>, notes: Vec<Note> },
+}
+
+impl Error {
+	// Attach the location the error occurred at; errors that already have a location keep it
+	pub fn at(self, span: &Span) -> Self {
+		match self {
+			Error::Located { .. } => self,
+			_ => Error::Located { span: span.clone(), error: Box::new(self) },
+		}
+	}
+
+	// Attach a note pointing at related source, e.g. the declaration of a symbol
+	pub fn with_note(self, span: &Span, message: &str) -> Self {
+		self.push_note(Note::new(Some(span.clone()), message.to_owned()))
+	}
+
+	fn push_note(self, note: Note) -> Self {
+		match self {
+			Error::Located { span, error } => Error::Located { span, error: Box::new(error.push_note(note)) },
+			Error::Noted { error, mut notes } => {
+				notes.push(note);
+				Error::Noted { error, notes }
+			},
+			_ => Error::Noted { error: Box::new(self), notes: vec![note] },
+		}
+	}
+
+	pub fn span(&self) -> Option<&Span> {
+		match self {
+			Error::Located { span, .. } => Some(span),
+			_ => None,
+		}
+	}
+
+	pub fn notes(&self) -> &[Note] {
+		match self {
+			Error::Located { error, .. } => error.notes(),
+			Error::Noted { notes, .. } => notes,
+			_ => &[],
+		}
+	}
+
+	// Stable code identifying the kind of error, for tools reading diagnostics; never reuse or renumber these
+	pub fn code(&self) -> &'static str {
+		match self.root() {
+			Error::FileOpenError { .. } => "E0001",
+			Error::FileReadError { .. } => "E0002",
+			Error::FileWriteError { .. } => "E0003",
+			Error::InvalidToken { .. } => "E0004",
+			Error::InvalidIdentifier { .. } => "E0005",
+			Error::TerminalTokenExpected { .. } => "E0006",
+			Error::UnknownToken { .. } => "E0007",
+			Error::UnknownIdentifier { .. } => "E0008",
+			Error::BinaryOperatorExpected { .. } => "E0009",
+			Error::IdentifierExpected { .. } => "E0010",
+			Error::LiteralExpected { .. } => "E0011",
+			Error::UnexpectedEOF { .. } => "E0012",
+			Error::UnexpectedLLVMValue { .. } => "E0013",
+			Error::StringParseError { .. } => "E0014",
+			Error::SymbolUndefined { .. } => "E0015",
+			Error::SymbolDeclared { .. } => "E0016",
+			Error::StatementExpected => "E0017",
+			Error::ExpressionExpected => "E0018",
+			Error::InvalidArithmeticOperand { .. } => "E0019",
+			Error::InvalidComparisonOperands { .. } => "E0020",
+			Error::InvalidAssignment { .. } => "E0021",
+			Error::TypeUnknown { .. } => "E0022",
+			Error::TypeExpected { .. } => "E0023",
+			Error::ArgumentMismatch { .. } => "E0024",
+			Error::UnexpectedFormat { .. } => "E0025",
+			Error::BadConversion { .. } => "E0026",
+			Error::InvalidDereference { .. } => "E0027",
+			Error::ExpectedLValue => "E0028",
+			Error::UnknownLint { .. } => "E0029",
+			Error::CompileFailed { .. } => "E0030",
+			Error::DivisionByZero => "E0031",
+			Error::InvalidPointer => "E0032",
+			Error::MissingReturn { .. } => "E0033",
+			Error::StackOverflow { .. } => "E0034",
+			Error::UnterminatedComment => "E0035",
+			Error::InvalidUnaryOperand { .. } => "E0036",
+			Error::OperandMismatch { .. } => "E0037",
+			Error::LiteralOutOfRange { .. } => "E0038",
+			Error::ConstantOverflow { .. } => "E0039",
+			Error::FloatParseError { .. } => "E0040",
+			Error::InvalidEscape { .. } => "E0041",
+			Error::InvalidCharLiteral { .. } => "E0042",
+			Error::UnterminatedLiteral { .. } => "E0043",
+			Error::ReadOnlyWrite => "E0044",
+			Error::InvalidFormatString { .. } => "E0045",
+			Error::FormatArgumentMismatch { .. } => "E0046",
+			Error::NotIndexable { .. } => "E0047",
+			Error::IndexNotInteger { .. } => "E0048",
+			Error::Unprintable { .. } => "E0049",
+			Error::InvalidArrayLength => "E0050",
+			Error::IndexOutOfBounds { .. } => "E0051",
+			Error::DuplicateField { .. } => "E0052",
+			Error::UnknownField { .. } => "E0053",
+			Error::MissingFields { .. } => "E0054",
+			Error::NotAStruct { .. } => "E0055",
+			Error::RecursiveStruct { .. } => "E0056",
+			Error::DuplicateVariant { .. } => "E0057",
+			Error::UnknownVariant { .. } => "E0058",
+			Error::DiscriminantOverflow { .. } => "E0059",
+			// root() never returns a wrapper
+			Error::Located { .. } | Error::Noted { .. } => unreachable!(),
+		}
+	}
+
+	// The underlying error without any location or notes attached
+	pub fn root(&self) -> &Error {
+		match self {
+			Error::Located { error, .. } | Error::Noted { error, .. } => error.root(),
+			_ => self,
+		}
+	}
 }
 
 impl fmt::Display for Error {
@@ -82,7 +239,12 @@ impl fmt::Display for Error {
 			Error::LiteralExpected { received } => write!(f, "LiteralExpected: Expected a Literal, but received {received}"),
 			Error::UnexpectedEOF { expected } => write!(f, "UnexpectedEOF: Expected {expected}, but reached EOF"),
 			Error::UnexpectedLLVMValue { expected, received } => write!(f, "UnexpectedLLVMValue: Expected a {expected_fmt}, but received {received_fmt}", expected_fmt=expected.val_type(), received_fmt=received.val_type()),
-			Error::StringParseError { cause } => write!(f, "StringParseError: {cause}"),
+			Error::StringParseError { literal, cause } => {
+				match cause.kind() {
+					IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => write!(f, "StringParseError: Literal '{literal}' doesn't fit in u64, the widest integer format"),
+					_ => write!(f, "StringParseError: Invalid literal '{literal}': {cause}"),
+				}
+			},
 			Error::SymbolUndefined { name } => write!(f, "SymbolUndefined: '{name}'"),
 			Error::SymbolDeclared { name } => write!(f, "SymbolDeclared: Symbol {name} has already been declared"),
 			Error::StatementExpected => write!(f, "StatementExpected: A statement was expected"),
@@ -98,7 +260,7 @@ impl fmt::Display for Error {
 			},
 			Error::InvalidArithmeticOperand { received } => write!(f, "InvalidArithmeticOperand: Attempted to perform arithmetic on {received}"),
 			Error::InvalidComparisonOperands { left, right } => write!(f, "InvalidComparisonOperands: Attempted to compare {left} and {right}"),
-			Error::InvalidAssignment { received, expected } => write!(f, "InvalidAssigment: Attempted to assign {} to {}", received.format_type(), expected.format_type()),
+			Error::InvalidAssignment { received, expected } => write!(f, "InvalidAssigment: Attempted to assign {received} to {expected}"),
 			Error::TypeUnknown { received } => write!(f, "TypeUnknown: '{received}'"),
 			Error::TypeExpected { received } => write!(f, "TypeExpected: Expected a type, but got {received}"),
 			Error::ArgumentMismatch { expected, received } => {
@@ -117,6 +279,47 @@ impl fmt::Display for Error {
 			Error::BadConversion { from, to } => write!(f, "BadConversion: Attempt to convert {from} to {to}"),
 			Error::InvalidDereference { received } => write!(f, "InvalidDereference: Attempt to dereference {received}"),
 			Error::ExpectedLValue => write!(f, "ExpectedLValue: Expected an LValue"),
+			Error::UnknownLint { name } => write!(f, "UnknownLint: '{name}' is not a lint"),
+			Error::CompileFailed { errors } => {
+				write!(f, "CompileFailed: {} error{}", errors.len(), if errors.len() == 1 { "" } else { "s" })?;
+
+				for error in errors {
+					write!(f, "\n{error}")?;
+				}
+
+				Ok(())
+			},
+			Error::DivisionByZero => write!(f, "DivisionByZero: Attempt to divide by zero"),
+			Error::InvalidPointer => write!(f, "InvalidPointer: Attempt to dereference a pointer that doesn't point to a live local"),
+			Error::MissingReturn { name } => write!(f, "MissingReturn: '{name}' reached its end without returning a value"),
+			Error::StackOverflow { limit } => write!(f, "StackOverflow: Calls nested deeper than {limit}"),
+			Error::UnterminatedComment => write!(f, "UnterminatedComment: Block comment is never closed"),
+			Error::InvalidUnaryOperand { operator, received } => write!(f, "InvalidUnaryOperand: Attempted to apply {operator} to {received}"),
+			Error::OperandMismatch { left, right } => write!(f, "OperandMismatch: Attempted to mix {left} and {right}"),
+			Error::LiteralOutOfRange { value, format } => write!(f, "LiteralOutOfRange: {value} doesn't fit in {format}"),
+			Error::ConstantOverflow { left, operator, right, format } => write!(f, "ConstantOverflow: {left} {operator} {right} doesn't fit in {format}"),
+			Error::FloatParseError { literal, cause } => write!(f, "FloatParseError: Invalid literal '{literal}': {cause}"),
+			Error::InvalidEscape { sequence } => write!(f, "InvalidEscape: '{sequence}' is not a valid escape sequence"),
+			Error::InvalidCharLiteral { literal } => write!(f, "InvalidCharLiteral: {literal} must be exactly one byte"),
+			Error::UnterminatedLiteral { literal } => write!(f, "UnterminatedLiteral: {literal} is missing its closing quote"),
+			Error::ReadOnlyWrite => write!(f, "ReadOnlyWrite: Attempt to write to a string literal"),
+			Error::InvalidFormatString { format } => write!(f, "InvalidFormatString: \"{format}\" has a brace that isn't part of {{}}; write {{{{ or }}}} for a literal one"),
+			Error::FormatArgumentMismatch { expected, received } => write!(f, "FormatArgumentMismatch: Format string has {expected} placeholder(s), but {received} argument(s) were given"),
+			Error::NotIndexable { received } => write!(f, "NotIndexable: Attempted to index {received}, which isn't an array"),
+			Error::IndexNotInteger { received } => write!(f, "IndexNotInteger: Array index must be an integer, but got {received}"),
+			Error::Unprintable { received } => write!(f, "Unprintable: Values of {received} can't be printed"),
+			Error::InvalidArrayLength => write!(f, "InvalidArrayLength: Array length must be a non-negative integer literal"),
+			Error::IndexOutOfBounds { len, index } => write!(f, "IndexOutOfBounds: The len is {len} but the index is {index}"),
+			Error::DuplicateField { name, field } => write!(f, "DuplicateField: Field {field} of {name} is given more than once"),
+			Error::UnknownField { name, field } => write!(f, "UnknownField: {name} has no field named {field}"),
+			Error::MissingFields { name, fields } => write!(f, "MissingFields: {name} literal is missing {}", fields.join(", ")),
+			Error::NotAStruct { received } => write!(f, "NotAStruct: Attempted to access a field of {received}, which isn't a struct"),
+			Error::RecursiveStruct { name } => write!(f, "RecursiveStruct: {name} contains itself, so it would have infinite size; use a pointer instead"),
+			Error::DuplicateVariant { name, variant } => write!(f, "DuplicateVariant: {name} declares {variant} more than once"),
+			Error::UnknownVariant { name, variant } => write!(f, "UnknownVariant: {name} has no variant named {variant}"),
+			Error::DiscriminantOverflow { name, variant, value } => write!(f, "DiscriminantOverflow: Discriminant {value} of {name}::{variant} doesn't fit in int, which enums are stored as"),
+			Error::Located { span, error } => write!(f, "{span}: {error}"),
+			Error::Noted { error, .. } => write!(f, "{error}"),
 		}
 	}
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+use crate::error::*;
+use crate::parsing::ast::ASTNode;
+use crate::scanning::token::*;
+use super::llvm::{Constant, RegisterFormat};
+
+// Fold an expression made only of number literals and arithmetic, bitwise and comparison operators to a constant,
+// or give None if it isn't one. Integer literals take the expected format if they fit in it and are int otherwise,
+// float ones take it if it is a float format and are f64 otherwise, and the operators are applied in the format of
+// their operands like the generated code would, so e.g. 1 << 31 can be a u32 and 1.0 / 3.0 an f32.
+// An integer result that doesn't fit its format is an error, except that bits shifted out to the left are dropped.
+// The interpreter folds the same expressions, so both give the same value for them.
+pub fn fold_constant(node: &ASTNode, expected: Option<&RegisterFormat>) -> Result<Option<Constant>> {
+	let folded = match node {
+		ASTNode::Literal { value: Literal::Integer(x), .. } => {
+			let value = i128::from(*x);
+			let format = match expected {
+				Some(format) if format.can_hold(value) => format.clone(),
+				_ => RegisterFormat::INT,
+			};
+
+			Ok(Constant::Integer { value, format })
+		},
+		ASTNode::Literal { value: Literal::Float(x), .. } => {
+			let format = match expected {
+				Some(format) if format.is_float() => format.clone(),
+				_ => RegisterFormat::F64,
+			};
+
+			Ok(Constant::Float { value: round(*x, &format), format })
+		},
+		ASTNode::Unary { token: token @ (Token::Minus | Token::Tilde), child, .. } => match fold_constant(child, expected)? {
+			Some(Constant::Integer { value, format }) => fold_unary(token, value, format),
+			Some(Constant::Float { value, format }) if *token == Token::Minus => Ok(Constant::Float { value: -value, format }),
+			Some(Constant::Float { format, .. }) => Err(Error::InvalidUnaryOperand { operator: token.clone(), received: format }),
+			_ => return Ok(None),
+		},
+		ASTNode::Binary { token, left, right, .. } if token.is_comparison() || token.is_bitwise() || token.is_arithmetic() => {
+			// A comparison gives a bool, so what is expected of it says nothing about its operands
+			let expected = if token.is_comparison() { None } else { expected };
+			let (Some(left), Some(right)) = (fold_constant(left, expected)?, fold_constant(right, expected)?) else {
+				return Ok(None);
+			};
+
+			match (left, right) {
+				(Constant::Integer { value: left, format: left_fmt }, Constant::Integer { value: right, format: right_fmt }) => fold_binary(token, (left, left_fmt), (right, right_fmt)),
+				(Constant::Float { value: left, format: left_fmt }, Constant::Float { value: right, format: right_fmt }) => fold_float_binary(token, (left, left_fmt), (right, right_fmt)),
+				// Comparisons of the bools they give are left to the generated code
+				(Constant::Boolean(_), _) | (_, Constant::Boolean(_)) => return Ok(None),
+				(left, right) => Err(Error::OperandMismatch { left: left.format(), right: right.format() }),
+			}
+		},
+		_ => return Ok(None),
+	};
+
+	folded.map(Some).map_err(|error| error.at(node.span()))
+}
+
+// Whether fold_constant folds node, which only depends on what it is made of and not on the format expected of it
+pub fn is_constant(node: &ASTNode) -> bool {
+	match node {
+		ASTNode::Binary { token, left, right, .. } if token.is_comparison() => is_number_constant(left) && is_number_constant(right),
+		node => is_number_constant(node),
+	}
+}
+
+fn is_number_constant(node: &ASTNode) -> bool {
+	match node {
+		ASTNode::Literal { value: Literal::Integer(_) | Literal::Float(_), .. } => true,
+		ASTNode::Unary { token: Token::Minus | Token::Tilde, child, .. } => is_number_constant(child),
+		ASTNode::Binary { token, left, right, .. } if token.is_bitwise() || token.is_arithmetic() => is_number_constant(left) && is_number_constant(right),
+		_ => false,
+	}
+}
+
+// Negative literals stay constants, so they can be used as any signed format they fit in.
+// ~ flips the bits of the format, so e.g. ~0 as a u8 is 255.
+fn fold_unary(token: &Token, value: i128, format: RegisterFormat) -> Result<Constant> {
+	let value = if let Token::Minus = token { -value } else { format.wrap(!value) };
+	// int constants are only checked once they are used, as they can still be given a format they fit in
+	if format != RegisterFormat::INT && !format.can_hold(value) {
+		return Err(Error::LiteralOutOfRange { value, format });
+	}
+
+	Ok(Constant::Integer { value, format })
+}
+
+fn fold_binary(token: &Token, (left, left_fmt): (i128, RegisterFormat), (right, right_fmt): (i128, RegisterFormat)) -> Result<Constant> {
+	// An int operand takes the format of the other one, like a literal does
+	let format = match (&left_fmt, &right_fmt) {
+		(&RegisterFormat::INT, format) | (format, &RegisterFormat::INT) => format.clone(),
+		_ if left_fmt == right_fmt => left_fmt,
+		_ => return Err(Error::OperandMismatch { left: left_fmt, right: right_fmt }),
+	};
+	let RegisterFormat::Integer { width, .. } = format else {
+		unreachable!("integer constants have integer formats");
+	};
+	for value in [left, right] {
+		if !format.can_hold(value) {
+			return Err(Error::LiteralOutOfRange { value, format });
+		}
+	}
+
+	let value = match token {
+		Token::Plus => left.checked_add(right),
+		Token::Minus => left.checked_sub(right),
+		Token::Asterisk => left.checked_mul(right),
+		Token::Slash | Token::Percent if right == 0 => return Err(Error::DivisionByZero),
+		Token::Slash => Some(left / right),
+		Token::Percent => Some(left % right),
+		Token::Ampersand => Some(left & right),
+		Token::Pipe => Some(left | right),
+		Token::Caret => Some(left ^ right),
+		// Shifting by the width or more is undefined in the generated code
+		Token::LessThan2 | Token::GreaterThan2 if right < 0 || right >= i128::from(width) => None,
+		Token::LessThan2 => Some(format.wrap(left << right)),
+		Token::GreaterThan2 => Some(left >> right),
+		Token::Equals2 => return Ok(Constant::Boolean(left == right)),
+		Token::ExclamationEqual => return Ok(Constant::Boolean(left != right)),
+		Token::LessThan => return Ok(Constant::Boolean(left < right)),
+		Token::LessThanEqual => return Ok(Constant::Boolean(left <= right)),
+		Token::GreaterThan => return Ok(Constant::Boolean(left > right)),
+		Token::GreaterThanEqual => return Ok(Constant::Boolean(left >= right)),
+		_ => return Err(Error::BinaryOperatorExpected { received: token.clone() }),
+	};
+
+	match value {
+		Some(value) if format.can_hold(value) => Ok(Constant::Integer { value, format }),
+		_ => Err(Error::ConstantOverflow { left, operator: token.clone(), right, format }),
+	}
+}
+
+// Floats are folded the way the generated code computes them, so division by zero gives an infinity or NaN
+fn fold_float_binary(token: &Token, (left, left_fmt): (f64, RegisterFormat), (right, right_fmt): (f64, RegisterFormat)) -> Result<Constant> {
+	if left_fmt != right_fmt {
+		return Err(Error::OperandMismatch { left: left_fmt, right: right_fmt });
+	}
+
+	let value = match token {
+		Token::Plus => left + right,
+		Token::Minus => left - right,
+		Token::Asterisk => left * right,
+		Token::Slash => left / right,
+		Token::Equals2 => return Ok(Constant::Boolean(left == right)),
+		Token::ExclamationEqual => return Ok(Constant::Boolean(left != right)),
+		Token::LessThan => return Ok(Constant::Boolean(left < right)),
+		Token::LessThanEqual => return Ok(Constant::Boolean(left <= right)),
+		Token::GreaterThan => return Ok(Constant::Boolean(left > right)),
+		Token::GreaterThanEqual => return Ok(Constant::Boolean(left >= right)),
+		_ => return Err(Error::InvalidArithmeticOperand { received: left_fmt }),
+	};
+
+	Ok(Constant::Float { value: round(value, &left_fmt), format: left_fmt })
+}
+
+// f32 values are rounded to single precision after each operation, like they are when computed in f32
+fn round(value: f64, format: &RegisterFormat) -> f64 {
+	match format {
+		RegisterFormat::Float { width: 32 } => value as f32 as f64,
+		_ => value,
+	}
+}
\ No newline at end of file
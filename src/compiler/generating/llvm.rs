@@ -1,5 +1,6 @@
 use core::fmt;
 use crate::error::{Error, Result};
+use crate::scanning::span::Span;
 
 use super::{Identifier, Token};
 
@@ -19,7 +20,7 @@ impl LLVMValue {
 			LLVMValue::Null => String::from("null"),
 			LLVMValue::Constant(c) => c.const_type(),
 			LLVMValue::VirtualRegister(v) => v.reg_type(),
-			LLVMValue::Indirect { pointee, referenced_fmt } => format!("{}", referenced_fmt.format_type()),
+			LLVMValue::Indirect { referenced_fmt, .. } => referenced_fmt.format_type().to_string(),
 		}
 	}
 
@@ -29,7 +30,7 @@ impl LLVMValue {
 			LLVMValue::Null => RegisterFormat::Null,
 			LLVMValue::Constant(c) => c.format(),
 			LLVMValue::VirtualRegister(r) => r.format().clone(),
-			LLVMValue::Indirect { pointee, referenced_fmt } => pointee.format(),
+			LLVMValue::Indirect { pointee, .. } => pointee.format(),
 		}
 	}
 }
@@ -41,26 +42,60 @@ impl std::fmt::Display for LLVMValue {
 			LLVMValue::Null => write!(f, "null"),
 			LLVMValue::VirtualRegister(vr) => write!(f, "{vr}"),
 			LLVMValue::Constant(c) => write!(f, "{c}"),
-			LLVMValue::Indirect { pointee, referenced_fmt } => write!(f, "{pointee}"),
+			LLVMValue::Indirect { pointee, .. } => write!(f, "{pointee}"),
 		}
 	}
 }
 
 #[derive(Debug, Clone)]
 pub enum Constant {
-	Integer(i64),
+	// Wide enough for the range of both i64 and u64, as which of them a literal is used as isn't known when it is read
+	Integer { value: i128, format: RegisterFormat },
+	Float { value: f64, format: RegisterFormat },
+	Char(u8),
+	// Address of the first byte of the string global with the index; len counts the NUL
+	String { index: usize, len: usize },
+	Boolean(bool),
+	Array { elements: Vec<Constant>, format: RegisterFormat },
+	// Fields in the order they are declared in
+	Struct { fields: Vec<Constant>, format: RegisterFormat },
+	// Start of an array or struct that is built up an element at a time
+	Undefined(RegisterFormat),
 }
 
 impl Constant {
+	// An int constant, which is what integer literals are until they are used as another integer format
+	pub fn int(value: i128) -> Self {
+		Constant::Integer { value, format: RegisterFormat::INT }
+	}
+
+	// An f64 constant, which is what float literals are until they are used as f32
+	pub fn float(value: f64) -> Self {
+		Constant::Float { value, format: RegisterFormat::F64 }
+	}
+
 	pub fn const_type(&self) -> String {
+		self.format().format_type()
+	}
+
+	// Whether every bit of the constant is zero, so arrays and structs of it can be written as zeroinitializer
+	pub fn is_zero(&self) -> bool {
 		match self {
-			Constant::Integer(_) => String::from("i64"),
+			Constant::Integer { value, .. } => *value == 0,
+			Constant::Float { value, .. } => value.to_bits() == 0,
+			Constant::Char(x) => *x == 0,
+			Constant::Boolean(x) => !x,
+			Constant::Array { elements, .. } | Constant::Struct { fields: elements, .. } => elements.iter().all(Constant::is_zero),
+			Constant::String { .. } | Constant::Undefined(_) => false,
 		}
 	}
 
 	pub fn format(&self) -> RegisterFormat {
 		match self {
-			Constant::Integer(_) => RegisterFormat::Integer,
+			Constant::Integer { format, .. } | Constant::Float { format, .. } | Constant::Array { format, .. } | Constant::Struct { format, .. } | Constant::Undefined(format) => format.clone(),
+			Constant::Char(_) => RegisterFormat::Char,
+			Constant::String { .. } => RegisterFormat::Char.to_pointer(),
+			Constant::Boolean(_) => RegisterFormat::Boolean,
 		}
 	}
 }
@@ -68,7 +103,23 @@ impl Constant {
 impl fmt::Display for Constant {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
-			Constant::Integer(x) => write!(f, "{x}"),
+			Constant::Integer { value, .. } => write!(f, "{value}"),
+			// Written as the bits of a double, which LLVM needs to be exactly representable as a float for f32
+			Constant::Float { value, format: RegisterFormat::Float { width: 32 } } => write!(f, "0x{:016X}", (*value as f32 as f64).to_bits()),
+			Constant::Float { value, .. } => write!(f, "0x{:016X}", value.to_bits()),
+			Constant::Char(x) => write!(f, "{}", *x as i8),
+			Constant::String { index, len } => write!(f, "getelementptr inbounds ([{len} x i8], [{len} x i8]* @str.{index}, i64 0, i64 0)"),
+			Constant::Boolean(x) => write!(f, "{x}"),
+			Constant::Array { .. } | Constant::Struct { .. } if self.is_zero() => write!(f, "zeroinitializer"),
+			Constant::Array { elements, .. } => {
+				let elements: Vec<String> = elements.iter().map(|element| format!("{} {element}", element.const_type())).collect();
+				write!(f, "[{}]", elements.join(", "))
+			},
+			Constant::Struct { fields, .. } => {
+				let fields: Vec<String> = fields.iter().map(|field| format!("{} {field}", field.const_type())).collect();
+				write!(f, "{{ {} }}", fields.join(", "))
+			},
+			Constant::Undefined(_) => write!(f, "undef"),
 		}
 	}
 }
@@ -95,10 +146,9 @@ impl VirtualRegister {
 			format: match &identifier {
 				Identifier::Symbol(s) => {
 					RegisterFormat::Identifier {
-						id_type: Box::new(symbol_table.get(&s)?.value().format())
+						id_type: Box::new(symbol_table.get(s)?.value().format())
 					}
 				},
-				_ => Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: identifier })?
 			},
 			is_local,
 		})
@@ -138,9 +188,9 @@ pub struct FunctionSignature {
 }
 
 impl FunctionSignature {
-	pub fn new(params: &Vec<RegisterFormat>, return_fmt: RegisterFormat) -> Self {
+	pub fn new(params: &[RegisterFormat], return_fmt: RegisterFormat) -> Self {
 		Self {
-			params: params.clone(),
+			params: params.to_vec(),
 			return_fmt: Box::new(return_fmt),
 		}
 	}
@@ -165,7 +215,121 @@ impl fmt::Display for FunctionSignature {
 			}
 		}
 
-		write!(f, "{out}")
+		write!(f, "{out}) -> {}", self.return_fmt)
+	}
+}
+
+// Fields of a struct, in the order they are laid out in
+#[derive(Debug, Clone)]
+pub struct StructLayout {
+	name: String,
+	fields: Vec<(String, RegisterFormat)>,
+	span: Span,
+}
+
+impl StructLayout {
+	pub fn new(name: &str, span: &Span) -> Self {
+		Self {
+			name: name.to_owned(),
+			fields: Vec::new(),
+			span: span.clone(),
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn fields(&self) -> &[(String, RegisterFormat)] {
+		&self.fields
+	}
+
+	// Where the struct was declared
+	pub fn span(&self) -> &Span {
+		&self.span
+	}
+
+	pub fn format(&self) -> RegisterFormat {
+		RegisterFormat::Struct { name: self.name.clone() }
+	}
+
+	pub fn push_field(&mut self, name: &str, format: RegisterFormat) -> Result<()> {
+		if self.field(name).is_ok() {
+			return Err(Error::DuplicateField { name: self.name.clone(), field: name.to_owned() });
+		}
+
+		self.fields.push((name.to_owned(), format));
+		Ok(())
+	}
+
+	// Index and format of the named field
+	pub fn field(&self, name: &str) -> Result<(usize, &RegisterFormat)> {
+		self.fields.iter()
+			.position(|field| field.0 == name)
+			.map(|index| (index, &self.fields[index].1))
+			.ok_or_else(|| Error::UnknownField { name: self.name.clone(), field: name.to_owned() })
+	}
+}
+
+// Discriminants of the variants of an enum, in the order they are declared in
+#[derive(Debug, Clone)]
+pub struct EnumLayout {
+	name: String,
+	variants: Vec<(String, i64)>,
+	span: Span,
+}
+
+impl EnumLayout {
+	pub fn new(name: &str, span: &Span) -> Self {
+		Self {
+			name: name.to_owned(),
+			variants: Vec::new(),
+			span: span.clone(),
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn variants(&self) -> &[(String, i64)] {
+		&self.variants
+	}
+
+	// Where the enum was declared
+	pub fn span(&self) -> &Span {
+		&self.span
+	}
+
+	pub fn format(&self) -> RegisterFormat {
+		RegisterFormat::Enum { name: self.name.clone() }
+	}
+
+	// Add a variant; without a discriminant it is one more than the variant before it, or 0 if it is the first
+	pub fn push_variant(&mut self, name: &str, discriminant: Option<i128>) -> Result<()> {
+		if self.variant(name).is_ok() {
+			return Err(Error::DuplicateVariant { name: self.name.clone(), variant: name.to_owned() });
+		}
+
+		let discriminant = match (discriminant, self.variants.last()) {
+			(Some(discriminant), _) => discriminant,
+			(None, Some((_, last))) => i128::from(*last) + 1,
+			(None, None) => 0,
+		};
+		let Ok(discriminant) = i64::try_from(discriminant) else {
+			return Err(Error::DiscriminantOverflow { name: self.name.clone(), variant: name.to_owned(), value: discriminant });
+		};
+
+		self.variants.push((name.to_owned(), discriminant));
+		Ok(())
+	}
+
+	// Discriminant of the named variant
+	pub fn variant(&self, name: &str) -> Result<i64> {
+		self.variants.iter()
+			.find(|variant| variant.0 == name)
+			.map(|variant| variant.1)
+			.ok_or_else(|| Error::UnknownVariant { name: self.name.clone(), variant: name.to_owned() })
 	}
 }
 
@@ -173,7 +337,15 @@ impl fmt::Display for FunctionSignature {
 pub enum RegisterFormat {
 	Void,
 	Null,
-	Integer,
+	Integer {
+		signed: bool,
+		width: u32,
+	},
+	Float {
+		width: u32,
+	},
+	// A byte, compared as unsigned
+	Char,
 	Boolean,
 	Identifier {
 		id_type: Box<RegisterFormat>,
@@ -181,29 +353,98 @@ pub enum RegisterFormat {
 	Pointer {
 		pointee: Box<RegisterFormat>,
 	},
+	Array {
+		element: Box<RegisterFormat>,
+		len: usize,
+	},
+	// Its fields are looked up by name in the generator's StructLayouts
+	Struct {
+		name: String,
+	},
+	// Held as an int, but only comparable with == and != to the same enum; 'as' converts it to and from integers
+	Enum {
+		name: String,
+	},
 	Function {
 		signature: FunctionSignature,
 	}
 }
 
 impl RegisterFormat {
+	// int, the same format as i64
+	pub const INT: RegisterFormat = RegisterFormat::Integer { signed: true, width: 64 };
+	pub const F64: RegisterFormat = RegisterFormat::Float { width: 64 };
+
 	pub fn to_pointer(&self) -> RegisterFormat {
 		RegisterFormat::Pointer { pointee: Box::new(self.clone()) }
 	}
 
+	pub fn is_integer(&self) -> bool {
+		matches!(self, RegisterFormat::Integer { .. })
+	}
+
+	pub fn is_float(&self) -> bool {
+		matches!(self, RegisterFormat::Float { .. })
+	}
+
+	// Chars are converted like u8 and enums like int
+	pub fn as_integer(&self) -> RegisterFormat {
+		match self {
+			RegisterFormat::Char => RegisterFormat::Integer { signed: false, width: 8 },
+			RegisterFormat::Enum { .. } => RegisterFormat::INT,
+			_ => self.clone(),
+		}
+	}
+
+	// Whether values of the format can be used with + - * /
+	pub fn is_numeric(&self) -> bool {
+		self.is_integer() || self.is_float()
+	}
+
+	// Whether the format is a signed integer; this picks e.g. sdiv over udiv
+	pub fn is_signed(&self) -> bool {
+		matches!(self, RegisterFormat::Integer { signed: true, .. })
+	}
+
+	// Whether an integer value can be represented in this format
+	pub fn can_hold(&self, value: i128) -> bool {
+		match *self {
+			RegisterFormat::Integer { signed: true, width } => value >> (width - 1) == 0 || value >> (width - 1) == -1,
+			RegisterFormat::Integer { signed: false, width } => value >= 0 && value >> width == 0,
+			_ => false,
+		}
+	}
+
+	// Wrap an integer value to the width of this format, like arithmetic in the generated code does
+	pub fn wrap(&self, value: i128) -> i128 {
+		match *self {
+			RegisterFormat::Integer { signed: true, width } => value << (128 - width) >> (128 - width),
+			RegisterFormat::Integer { signed: false, width } => ((value as u128) << (128 - width) >> (128 - width)) as i128,
+			_ => value,
+		}
+	}
+
+	// Only numbers of the same format can be compared; they can't be mixed without a conversion
 	pub fn can_compare_to(&self, other: &RegisterFormat, op: &Token) -> bool {
 		match (self, op, other) {
-			(RegisterFormat::Integer, _, RegisterFormat::Integer) => true,
+			(RegisterFormat::Integer { .. } | RegisterFormat::Float { .. } | RegisterFormat::Char, _, _) => self == other,
+			(RegisterFormat::Boolean, Token::Equals2 | Token::ExclamationEqual, RegisterFormat::Boolean) => true,
+			(RegisterFormat::Enum { .. }, Token::Equals2 | Token::ExclamationEqual, _) => self == other,
 			_ => false,
 		}
 	}
 
 	pub fn can_convert_to(&self, other: &RegisterFormat) -> bool {
 		match (self, other) {
-			(RegisterFormat::Integer, RegisterFormat::Integer) => true,
+			(RegisterFormat::Integer { .. }, RegisterFormat::Integer { .. }) => self == other,
+			(RegisterFormat::Float { .. }, RegisterFormat::Float { .. }) => self == other,
+			(RegisterFormat::Char, RegisterFormat::Char) => true,
 			(RegisterFormat::Boolean, RegisterFormat::Boolean) => true,
 			(RegisterFormat::Pointer { .. }, RegisterFormat::Boolean) => true,
-			(RegisterFormat::Pointer { pointee: self_pointee }, RegisterFormat::Pointer { pointee: other_pointee }) => self_pointee.can_convert_to(&other_pointee),
+			(RegisterFormat::Pointer { pointee: self_pointee }, RegisterFormat::Pointer { pointee: other_pointee }) => self_pointee.can_convert_to(other_pointee),
+			(RegisterFormat::Array { .. }, RegisterFormat::Array { .. }) => self == other,
+			(RegisterFormat::Struct { .. }, RegisterFormat::Struct { .. }) => self == other,
+			(RegisterFormat::Enum { .. }, RegisterFormat::Enum { .. }) => self == other,
 			_ => false,
 		}
 	}
@@ -211,10 +452,16 @@ impl RegisterFormat {
 	pub fn format_type(&self) -> String {
 		match self {
 			RegisterFormat::Void => String::from("void"),
-			RegisterFormat::Identifier { id_type } => String::from(format!("{}*", id_type.format_type())),
-			RegisterFormat::Integer => String::from("i64"),
+			RegisterFormat::Identifier { id_type } => format!("{}*", id_type.format_type()),
+			RegisterFormat::Integer { width, .. } => format!("i{width}"),
+			RegisterFormat::Float { width: 32 } => String::from("float"),
+			RegisterFormat::Float { .. } => String::from("double"),
+			RegisterFormat::Char => String::from("i8"),
 			RegisterFormat::Boolean => String::from("i1"),
-			RegisterFormat::Pointer { pointee } => String::from(format!("{}*", pointee.format_type())),
+			RegisterFormat::Pointer { pointee } => format!("{}*", pointee.format_type()),
+			RegisterFormat::Array { element, len } => format!("[{len} x {}]", element.format_type()),
+			RegisterFormat::Struct { name } => format!("%struct.{name}"),
+			RegisterFormat::Enum { .. } => RegisterFormat::INT.format_type(),
 			RegisterFormat::Function { .. } => String::from("function"),
 			RegisterFormat::Null => String::from("null"),
 		}
@@ -234,8 +481,14 @@ impl fmt::Display for RegisterFormat {
 		match self {
 			RegisterFormat::Void => write!(f, "void"),
 			RegisterFormat::Boolean => write!(f, "bool"),
-			RegisterFormat::Integer => write!(f, "int"),
+			RegisterFormat::Integer { signed: true, width: 64 } => write!(f, "int"),
+			RegisterFormat::Integer { signed: true, width } => write!(f, "i{width}"),
+			RegisterFormat::Integer { signed: false, width } => write!(f, "u{width}"),
+			RegisterFormat::Float { width } => write!(f, "f{width}"),
+			RegisterFormat::Char => write!(f, "char"),
 			RegisterFormat::Pointer { pointee } => write!(f, "{pointee}*"),
+			RegisterFormat::Array { element, len } => write!(f, "[{element}; {len}]"),
+			RegisterFormat::Struct { name } | RegisterFormat::Enum { name } => write!(f, "{name}"),
 			RegisterFormat::Identifier { id_type } => write!(f, "{id_type}"),
 			RegisterFormat::Function { .. } => write!(f, "function"),
 			RegisterFormat::Null => write!(f, "null"),
@@ -248,10 +501,12 @@ pub enum Symbol {
 	Local {
 		name: String,
 		value: LLVMValue,
+		span: Span,
 	},
 	Function {
 		name: String,
 		value: LLVMValue,
+		span: Span,
 	}
 }
 
@@ -263,6 +518,14 @@ impl Symbol {
 		}
 	}
 
+	// Where the symbol was declared
+	pub fn span(&self) -> &Span {
+		match self {
+			Symbol::Local { span, .. } => span,
+			Symbol::Function { span, .. } => span,
+		}
+	}
+
 	pub fn value(&self) -> &LLVMValue {
 		match self {
 			Symbol::Local { value, .. } => value,
@@ -313,6 +576,10 @@ impl SymbolTable {
 		self.buckets.len()
 	}
 
+	pub fn is_empty(&self) -> bool {
+		self.buckets.is_empty()
+	}
+
 	pub fn insert(&mut self, symbol: Symbol) {
 		let hash = self.hash(symbol.name());
 
@@ -362,14 +629,14 @@ impl SymbolTable {
 				let next = curr.as_mut().unwrap().next.take();
 				*curr = next;
 
-				return ();
+				return;
 			} else if curr.as_ref().unwrap().next().is_none() {
-				return ();
+				return;
 			} else if curr.as_ref().unwrap().next().as_ref().unwrap().symbol().name().eq(name) {
 				let next = curr.as_mut().unwrap().next.as_mut().unwrap().next.take();
 				*curr = next;
 
-				return ();
+				return;
 			} else {
 				curr = &mut curr.as_mut().unwrap().next;
 			}
@@ -391,15 +658,15 @@ impl SymbolTable {
 		let mut pow: u64 = 1;
 
 		let mut hash: u64 = 0;
-		for (_, c) in name.chars().enumerate() {
+		for c in name.chars() {
 			hash = (hash + (c as u64 % len) * pow) % len;
 			pow = (pow * prime) % len;
 		}
 
-		return hash as usize;
+		hash as usize
 	}
 
-	pub fn create_local(&self, name: &String, format: &RegisterFormat) -> (Symbol, VirtualRegister) {
+	pub fn create_local(&self, name: &String, format: &RegisterFormat, span: &Span) -> (Symbol, VirtualRegister) {
 		let pointer = VirtualRegister::new(name.to_owned(), format.to_pointer(), true);
 		let value = LLVMValue::Indirect {
 			pointee: Box::new(LLVMValue::VirtualRegister(pointer.clone())),
@@ -408,16 +675,18 @@ impl SymbolTable {
 		let symbol = Symbol::Local {
 			name: name.to_owned(),
 			value,
+			span: span.clone(),
 		};
 
 		(symbol, pointer)
 	}
 
-	pub fn create_function(&self, name: &String, signature: &FunctionSignature) -> (Symbol, VirtualRegister) {
+	pub fn create_function(&self, name: &String, signature: &FunctionSignature, span: &Span) -> (Symbol, VirtualRegister) {
 		let reg = VirtualRegister::new(name.to_owned(), RegisterFormat::Function { signature: signature.to_owned() }, false);
 		let symbol = Symbol::Function {
 			name: name.to_owned(),
 			value: LLVMValue::VirtualRegister(reg.clone()),
+			span: span.clone(),
 		};
 
 		(symbol, reg)
@@ -1,31 +1,86 @@
 pub mod writer;
 pub mod llvm;
+pub mod folding;
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::Write;
 
 use crate::error::*;
+use crate::error::lint::{Lint, Warning};
 
-use crate::parsing::ast::{ASTNode, FunctionParameter, Type};
-use crate::parsing::Parser;
+use crate::parsing::ast::{ASTNode, EnumVariant, FieldInitializer, FunctionParameter, StructField, Type};
+use crate::scanning::span::Span;
 use crate::scanning::token::*;
+use folding::{fold_constant, is_constant};
 use llvm::*;
 use writer::Writer;
 
 pub const TYPE_FORMATS: &[(&str, RegisterFormat)] = &[
 	("bool", RegisterFormat::Boolean),
-	("int", RegisterFormat::Integer),
+	("int", RegisterFormat::INT),
+	("i8", RegisterFormat::Integer { signed: true, width: 8 }),
+	("i16", RegisterFormat::Integer { signed: true, width: 16 }),
+	("i32", RegisterFormat::Integer { signed: true, width: 32 }),
+	("i64", RegisterFormat::INT),
+	("u8", RegisterFormat::Integer { signed: false, width: 8 }),
+	("u16", RegisterFormat::Integer { signed: false, width: 16 }),
+	("u32", RegisterFormat::Integer { signed: false, width: 32 }),
+	("u64", RegisterFormat::Integer { signed: false, width: 64 }),
+	("f32", RegisterFormat::Float { width: 32 }),
+	("f64", RegisterFormat::F64),
+	("char", RegisterFormat::Char),
 ];
 
+// A local or function that should be reported if it is never used
+#[derive(Debug)]
+struct Declaration {
+	name: String,
+	span: Span,
+	lint: Lint,
+	used: bool,
+}
+
+impl Declaration {
+	fn new(name: &str, span: &Span, lint: Lint) -> Self {
+		Self {
+			name: name.to_owned(),
+			span: span.clone(),
+			lint,
+			used: false,
+		}
+	}
+}
+
 #[derive(Debug)]
-pub struct Generator {
-	writer: Writer,
+pub struct Generator<W: Write = File> {
+	writer: Writer<W>,
 	next_register: u32,
 	free_register_count: u32,
 	label_count: u32,
 	local_symbol_table: SymbolTable,
 	global_symbol_table: SymbolTable,
+	errors: Vec<Error>,
+	warnings: Vec<Warning>,
+	local_declarations: Vec<Declaration>,
+	function_declarations: Vec<Declaration>,
+	// Bytes of each distinct string literal, indexed by the number of its global
+	strings: Vec<Vec<u8>>,
+	structs: Vec<StructLayout>,
+	enums: Vec<EnumLayout>,
+	bounds_checks: bool,
+}
+
+impl Generator<File> {
+	pub fn from_filename(filename: String) -> Result<Self> {
+		Writer::from_filename(filename)
+			.map(Self::new)
+			.map_err(|cause| Error::FileOpenError { cause })
+	}
 }
 
-impl Generator {
-	pub fn new(writer: Writer) -> Self {
+impl<W: Write> Generator<W> {
+	pub fn new(writer: Writer<W>) -> Self {
 		Self {
 			writer,
 			next_register: 1,
@@ -33,19 +88,30 @@ impl Generator {
 			label_count: 0,
 			local_symbol_table: SymbolTable::new(64),
 			global_symbol_table: SymbolTable::new(64),
+			errors: Vec::new(),
+			warnings: Vec::new(),
+			local_declarations: Vec::new(),
+			function_declarations: Vec::new(),
+			strings: Vec::new(),
+			structs: Vec::new(),
+			enums: Vec::new(),
+			bounds_checks: false,
 		}
 	}
 
-	pub fn from_filename(filename: String) -> Result<Self> {
-		Writer::from_filename(filename)
-			.map(|writer| Self::new(writer))
-			.map_err(|cause| Error::FileOpenError { cause })
+	// Check indexes that aren't constant at runtime, aborting with a message if they are out of bounds
+	pub fn set_bounds_checks(&mut self, bounds_checks: bool) {
+		self.bounds_checks = bounds_checks;
 	}
 
-	pub fn writer(&self) -> &Writer {
+	pub fn writer(&self) -> &Writer<W> {
 		&self.writer
 	}
 
+	pub fn into_writer(self) -> Writer<W> {
+		self.writer
+	}
+
 	pub fn next_virtual_register(&self) -> u32 {
 		self.next_register
 	}
@@ -58,19 +124,191 @@ impl Generator {
 		&self.global_symbol_table
 	}
 
-	pub fn generate(&mut self, parser: &mut Parser) -> Result<()> {
+	// Semantic errors recovered from so far; output is only valid if there are none
+	pub fn errors(&self) -> &[Error] {
+		&self.errors
+	}
+
+	pub fn take_errors(&mut self) -> Vec<Error> {
+		std::mem::take(&mut self.errors)
+	}
+
+	// Lint warnings found so far; whether they are reported is up to the caller
+	pub fn warnings(&self) -> &[Warning] {
+		&self.warnings
+	}
+
+	pub fn take_warnings(&mut self) -> Vec<Warning> {
+		std::mem::take(&mut self.warnings)
+	}
+
+	pub fn generate(&mut self, program: &[ASTNode]) -> Result<()> {
 		self.writer.write_preamble()?;
 
+		self.generate_types(program);
+		for layout in &self.structs {
+			self.writer.write_struct_type(layout)?;
+		}
+		if !self.structs.is_empty() {
+			self.writer.writeln("")?;
+		}
+
 		// Allocate variable stack space and write to output
-		while let Some(function) = parser.parse_global_statement()? {
+		for function in program {
 			self.free_register_count = self.next_register - 1;
 
-			self.ast_to_llvm(&function, None)?;
+			if let Err(error) = self.ast_to_llvm(function, None) {
+				// Function was abandoned part way through, so reset its state before the next one
+				self.errors.push(error);
+				self.free_register_count = 0;
+				self.next_register = 1;
+				self.local_symbol_table.clear();
+				self.local_declarations.clear();
+			}
+		}
+
+		// main is called by the runtime
+		let function_declarations = std::mem::take(&mut self.function_declarations);
+		self.warn_unused(function_declarations.into_iter().filter(|function| function.name != "main"));
+
+		for (index, bytes) in self.strings.iter().enumerate() {
+			self.writer.write_string_constant(index, bytes)?;
 		}
 
 		self.writer.write_postamble()?;
+		self.writer.flush()
+	}
 
-		Ok(())
+	// Lay out every struct and number the variants of every enum before any function is generated,
+	// so types and functions can be declared in any order
+	fn generate_types(&mut self, program: &[ASTNode]) {
+		// Every name is known before any field is looked at, as fields can be of types declared after them
+		let mut definitions: Vec<(usize, &[StructField])> = Vec::new();
+		for node in program {
+			let (ASTNode::StructDefinition { name, span, .. } | ASTNode::EnumDefinition { name, span, .. }) = node else {
+				continue;
+			};
+
+			if let Some(declared) = self.declared_type_span(name) {
+				self.errors.push(Error::SymbolDeclared { name: name.to_owned() }.at(span).with_note(&declared, "previously declared here"));
+				continue;
+			}
+			if TYPE_FORMATS.iter().any(|type_fmt| type_fmt.0 == name) {
+				self.errors.push(Error::SymbolDeclared { name: name.to_owned() }.at(span));
+				continue;
+			}
+
+			match node {
+				ASTNode::StructDefinition { fields, .. } => {
+					definitions.push((self.structs.len(), fields));
+					self.structs.push(StructLayout::new(name, span));
+				},
+				ASTNode::EnumDefinition { variants, .. } => self.generate_enum(name, variants, span),
+				_ => {},
+			}
+		}
+
+		for (index, fields) in definitions {
+			for field in fields {
+				let result = self.get_format_from_type(&field.field_type)
+					.and_then(|format| self.structs[index].push_field(&field.name, format));
+				if let Err(error) = result {
+					self.errors.push(error.at(&field.span));
+				}
+			}
+		}
+
+		for layout in &self.structs {
+			let target = layout.format();
+			if layout.fields().iter().any(|(_, format)| self.contains_struct(format, &target, &mut Vec::new())) {
+				self.errors.push(Error::RecursiveStruct { name: layout.name().to_owned() }.at(layout.span()));
+			}
+		}
+	}
+
+	// Number the variants of an enum
+	fn generate_enum(&mut self, name: &str, variants: &[EnumVariant], span: &Span) {
+		let mut layout = EnumLayout::new(name, span);
+		for variant in variants {
+			if let Err(error) = layout.push_variant(&variant.name, variant.discriminant) {
+				self.errors.push(error.at(&variant.span));
+			}
+		}
+
+		self.enums.push(layout);
+	}
+
+	// Where the struct or enum with the name was declared, if there is one
+	fn declared_type_span(&self, name: &str) -> Option<Span> {
+		self.get_struct(name).map(|layout| layout.span().clone())
+			.or_else(|_| self.get_enum(name).map(|layout| layout.span().clone()))
+			.ok()
+	}
+
+	// Whether a value of format holds a value of the struct target, rather than a pointer to one
+	fn contains_struct(&self, format: &RegisterFormat, target: &RegisterFormat, seen: &mut Vec<RegisterFormat>) -> bool {
+		match format {
+			RegisterFormat::Array { element, .. } => self.contains_struct(element, target, seen),
+			RegisterFormat::Struct { .. } if format == target => true,
+			RegisterFormat::Struct { name } if !seen.contains(format) => {
+				seen.push(format.clone());
+				self.get_struct(name).is_ok_and(|layout| layout.fields().iter().any(|(_, field)| self.contains_struct(field, target, seen)))
+			},
+			_ => false,
+		}
+	}
+
+	// Layout of the named struct
+	fn get_struct(&self, name: &str) -> Result<&StructLayout> {
+		self.structs.iter()
+			.find(|layout| layout.name() == name)
+			.ok_or_else(|| Error::TypeUnknown { received: Type::Named { type_name: name.to_owned() } })
+	}
+
+	// Variants of the named enum
+	fn get_enum(&self, name: &str) -> Result<&EnumLayout> {
+		self.enums.iter()
+			.find(|layout| layout.name() == name)
+			.ok_or_else(|| Error::TypeUnknown { received: Type::Named { type_name: name.to_owned() } })
+	}
+
+	// Generate each statement of a block, recording errors so the rest of the block still gets checked
+	pub fn generate_block(&mut self, block: &[ASTNode], expected_fmt: &Option<RegisterFormat>) {
+		let mut return_span: Option<&Span> = None;
+		for statement in block {
+			// Only the first unreachable statement is reported
+			if let Some(span) = return_span.take() {
+				self.warnings.push(Warning::new(Lint::UnreachableCode, String::from("unreachable statement"), statement.span().clone())
+					.with_note(span, "any code following this return is unreachable"));
+			}
+
+			if let ASTNode::Return { span, .. } = statement {
+				return_span = Some(span);
+			}
+
+			if let Err(error) = self.ast_to_llvm(statement, expected_fmt.to_owned()) {
+				self.errors.push(error);
+			}
+		}
+	}
+
+	// Warn about each declaration that was never used
+	fn warn_unused(&mut self, declarations: impl Iterator<Item = Declaration>) {
+		for declaration in declarations.filter(|declaration| !declaration.used) {
+			let message = match declaration.lint {
+				Lint::UnusedParameters => format!("unused parameter '{}'", declaration.name),
+				Lint::UnusedFunctions => format!("function '{}' is never called", declaration.name),
+				_ => format!("unused variable '{}'", declaration.name),
+			};
+			self.warnings.push(Warning::new(declaration.lint, message, declaration.span));
+		}
+	}
+
+	// Record that the named declaration was used
+	fn mark_used(declarations: &mut [Declaration], name: &str) {
+		if let Some(declaration) = declarations.iter_mut().rev().find(|declaration| declaration.name == name) {
+			declaration.used = true;
+		}
 	}
 
 	// Claim next register value and update next register
@@ -96,48 +334,131 @@ impl Generator {
 
 	// Traverse AST and generate LLVM for the tree
 	pub fn ast_to_llvm(&mut self, root: &ASTNode, expected_fmt: Option<RegisterFormat>) -> Result<LLVMValue> {
-		match root {
-			ASTNode::Literal(x) => Ok(self.generate_literal(x)?),
-			ASTNode::Binary {token, left, right} => Ok(self.generate_binary(token, *(*left).clone(), *(*right).clone())?),
-			ASTNode::Let { name, val_type, value } => Ok(self.generate_let(name, val_type, value)?),
-			ASTNode::If { expr, block, else_block } => Ok(self.generate_if(expr, block, else_block, &expected_fmt)?),
-			ASTNode::While { expr, block } => Ok(self.generate_while(expr, block, &expected_fmt)?),
-			ASTNode::FunctionDefinition { name, parameters, body_block, return_type } => Ok(self.generate_function(name.to_owned(), parameters, body_block, return_type)?),
-			ASTNode::Return { return_val } => Ok(self.generate_return(return_val, &expected_fmt)?),
-			ASTNode::FunctionCall { name, args } => Ok(self.generate_function_call(name, args)?),
-			ASTNode::Print { expr } => Ok(self.generate_print(expr)?),
-			ASTNode::Dereference { child } => Ok(self.generate_deref(child)?),
-			ASTNode::Reference { child } => Ok(self.generate_ref(child)?),
+		if let Some(constant) = fold_constant(root, None)? {
+			return Ok(LLVMValue::Constant(constant));
+		}
+
+		let res = match root {
+			ASTNode::Literal { value, .. } => self.generate_literal(value),
+			ASTNode::Binary { token, left, right, .. } => self.generate_binary(token, left, right),
+			ASTNode::Let { name, name_span, val_type, type_span, value, span } => self.generate_let(name, name_span, val_type, type_span, value, span),
+			ASTNode::If { expr, block, else_block, .. } => self.generate_if(expr, block, else_block, &expected_fmt),
+			ASTNode::While { expr, block, .. } => self.generate_while(expr, block, &expected_fmt),
+			ASTNode::FunctionDefinition { name, parameters, body_block, return_type, signature_span, .. } => self.generate_function(name.to_owned(), parameters, body_block, return_type, signature_span),
+			// Laid out by generate_types before any function
+			ASTNode::StructDefinition { .. } | ASTNode::EnumDefinition { .. } => Ok(LLVMValue::None),
+			ASTNode::Return { return_val, .. } => self.generate_return(return_val, &expected_fmt),
+			ASTNode::FunctionCall { name, args, .. } => self.generate_function_call(name, args),
+			ASTNode::Print { expr, .. } => self.generate_print(expr),
+			ASTNode::FormattedPrint { pieces, args, .. } => self.generate_formatted_print(pieces, args),
+			ASTNode::Unary { token, child, .. } => self.generate_unary(token, child),
+			ASTNode::Postfix { token, child, .. } => self.generate_increment(token, child, true),
+			ASTNode::Cast { child, target_type, .. } => self.generate_cast(child, target_type),
+			ASTNode::ArrayLiteral { elements, .. } => self.generate_array_literal(elements, None),
+			ASTNode::ArrayRepeat { value, count, .. } => self.generate_array_repeat(value, *count, None),
+			ASTNode::Index { child, index, .. } => self.generate_index(child, index),
+			ASTNode::StructLiteral { name, fields, .. } => self.generate_struct_literal(name, fields),
+			ASTNode::FieldAccess { child, field, .. } => self.generate_field(child, field),
+			ASTNode::Path { type_name, member, .. } => self.generate_path(type_name, member),
+			ASTNode::Dereference { child, .. } => self.generate_deref(child),
+			ASTNode::Reference { child, .. } => self.generate_ref(child),
+		};
+
+		// Errors are reported at the innermost node they occurred in
+		res.map_err(|error| error.at(root.span()))
+	}
+
+	// Generate an expression whose value is used as format, so that if it is made only of literals it is folded as that format
+	pub fn generate_expecting(&mut self, node: &ASTNode, format: &RegisterFormat) -> Result<LLVMValue> {
+		// The elements of an array literal are used as the element format
+		match (node, format) {
+			(ASTNode::ArrayLiteral { elements, .. }, RegisterFormat::Array { element, .. }) => self.generate_array_literal(elements, Some(element)).map_err(|error| error.at(node.span())),
+			(ASTNode::ArrayRepeat { value, count, .. }, RegisterFormat::Array { element, .. }) => self.generate_array_repeat(value, *count, Some(element)).map_err(|error| error.at(node.span())),
+			_ => match fold_constant(node, Some(format))? {
+				Some(constant) => Ok(LLVMValue::Constant(constant)),
+				None => self.ast_to_llvm(node, None),
+			},
 		}
+	}
 
+	// Format of the value an operand gives, rather than of its address if it is an lvalue
+	fn operand_format(value: &LLVMValue) -> RegisterFormat {
+		match value {
+			LLVMValue::Indirect { referenced_fmt, .. } => referenced_fmt.clone(),
+			value => value.format(),
+		}
 	}
 
 	// Generate literal value based on given type
 	pub fn generate_literal(&mut self, literal: &Literal) -> Result<LLVMValue> {
 		match literal {
-			Literal::Integer(x) => Ok(LLVMValue::Constant(Constant::Integer(*x))),
-			Literal::Identifier(i) => match i {
-				Identifier::Symbol(x) => Ok(self.local_symbol_table.get(x)?.value().to_owned()),
-				_ => Err(Error::TerminalTokenExpected { received_token: None, received_identifier: Some(i.clone()) })
+			Literal::Integer(x) => Ok(LLVMValue::Constant(Constant::int((*x).into()))),
+			Literal::Float(x) => Ok(LLVMValue::Constant(Constant::float(*x))),
+			Literal::Char(x) => Ok(LLVMValue::Constant(Constant::Char(*x))),
+			Literal::String(bytes) => Ok(self.intern_string(bytes)),
+			Literal::Boolean(x) => Ok(LLVMValue::Constant(Constant::Boolean(*x))),
+			Literal::Identifier(Identifier::Symbol(x)) => {
+				let value = self.local_symbol_table.get(x)?.value().to_owned();
+				Self::mark_used(&mut self.local_declarations, x);
+				Ok(value)
 			},
 		}
 	}
 
+	// Address of the global holding bytes; equal strings share a global
+	fn intern_string(&mut self, bytes: &[u8]) -> LLVMValue {
+		let index = self.strings.iter().position(|string| string == bytes).unwrap_or_else(|| {
+			self.strings.push(bytes.to_vec());
+			self.strings.len() - 1
+		});
+
+		LLVMValue::Constant(Constant::String { index, len: bytes.len() + 1 })
+	}
+
 	// Generate binary statement given operation and left/right LLVMValues
-	pub fn generate_binary(&mut self, token: &Token, left: ASTNode, right: ASTNode) -> Result<LLVMValue> {
-		let left = self.ast_to_llvm(&left, None)?;
-		let right = self.ast_to_llvm(&right, None)?;
+	pub fn generate_binary(&mut self, token: &Token, left: &ASTNode, right: &ASTNode) -> Result<LLVMValue> {
+		// Logical operators decide whether their right operand is evaluated at all
+		if token.is_logical() {
+			return self.generate_logical(token, left, right);
+		}
 
+		// An operand made only of literals is folded as the format of the other one. Only one of them can be,
+		// as otherwise the whole expression would have been folded, and if it is the left one it has no side effects.
+		let (left, right) = if is_constant(left) && !is_constant(right) {
+			let right = self.ast_to_llvm(right, None)?;
+			(self.generate_expecting(left, &Self::operand_format(&right))?, right)
+		} else {
+			let left = self.ast_to_llvm(left, None)?;
+			let right = self.generate_expecting(right, &Self::operand_format(&left))?;
+			(left, right)
+		};
+
+		// left op= right is stored through the address left was evaluated to, so left is only evaluated once
+		if let Some(operator) = token.compound_operator() {
+			let mut target = left.clone();
+			self.ensure_lvalue(&mut target)?;
+			let value = self.generate_operation(&operator, left.clone(), right)?;
+			return self.generate_assign(left, value);
+		}
+
+		self.generate_operation(token, left, right)
+	}
+
+	// Apply a binary operator to already generated operands
+	pub fn generate_operation(&mut self, token: &Token, left: LLVMValue, right: LLVMValue) -> Result<LLVMValue> {
 		let out = match token {
 			Token::Asterisk => Ok(self.generate_mul(left, right)?),
 			Token::Minus => Ok(self.generate_sub(left, right)?),
 			Token::Plus => Ok(self.generate_add(left, right)?),
 			Token::Slash => Ok(self.generate_div(left, right)?),
+			Token::Percent => Ok(self.generate_rem(left, right)?),
 			Token::Equals => Ok(self.generate_assign(left, right)?),
 			_ => {
 				// If token is a comparison operator, generate a comparison
 				if token.is_comparison() {
 					Ok(self.generate_comparison(token.to_owned(), left, right)?)
+				} else if token.is_bitwise() {
+					Ok(self.generate_bitwise(token, left, right)?)
 				} else {
 					Err(Error::BinaryOperatorExpected { received: token.clone() })
 				}
@@ -147,6 +468,166 @@ impl Generator {
 		Ok(out)
 	}
 
+	// Generate a prefix operator: -x is 0 - x for integers and fneg for floats, !x and ~x flip every bit of x
+	pub fn generate_unary(&mut self, token: &Token, child: &ASTNode) -> Result<LLVMValue> {
+		if token.is_increment() {
+			return self.generate_increment(token, child, false);
+		}
+
+		let mut val = self.ast_to_llvm(child, None)?;
+		self.ensure_rvalue(&mut val)?;
+
+		let operand_fmt = val.format();
+		let valid = match token {
+			Token::Minus => operand_fmt.is_numeric(),
+			Token::Tilde => operand_fmt.is_integer(),
+			Token::Exclamation => operand_fmt == RegisterFormat::Boolean,
+			_ => return Err(Error::BinaryOperatorExpected { received: token.clone() }),
+		};
+		if !valid {
+			return Err(Error::InvalidUnaryOperand { operator: token.clone(), received: operand_fmt });
+		}
+
+		// Negated literals were folded already
+		if *token == Token::Minus && operand_fmt.is_float() {
+			let reg = self.update_virtual_register(1);
+			self.writer.write_fneg(&val, reg)?;
+			return Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), operand_fmt, true)));
+		}
+
+		let flipped = match token {
+			Token::Minus => return self.generate_sub(LLVMValue::Constant(Constant::int(0)), val),
+			Token::Exclamation => Constant::Boolean(true),
+			_ => Constant::Integer { value: -1, format: operand_fmt.clone() },
+		};
+		let reg = self.update_virtual_register(1);
+		self.writer.write_xor(&val, &LLVMValue::Constant(flipped), reg)?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), operand_fmt, true)))
+	}
+
+	// Generate ++ or -- of an lvalue, giving the value it had before if postfix and the one it has after otherwise
+	pub fn generate_increment(&mut self, token: &Token, child: &ASTNode, postfix: bool) -> Result<LLVMValue> {
+		let target = self.ast_to_llvm(child, None)?;
+		let mut address = target.clone();
+		self.ensure_lvalue(&mut address)?;
+
+		let mut before = target.clone();
+		self.ensure_rvalue(&mut before)?;
+		if !before.format().is_integer() {
+			return Err(Error::InvalidUnaryOperand { operator: token.clone(), received: before.format() });
+		}
+
+		let one = LLVMValue::Constant(Constant::int(1));
+		let after = match token {
+			Token::Plus2 => self.generate_add(before.clone(), one)?,
+			_ => self.generate_sub(before.clone(), one)?,
+		};
+		self.generate_assign(target, after.clone())?;
+
+		Ok(if postfix { before } else { after })
+	}
+
+	// Generate expr as Type. Integers are sign extended if signed and zero extended otherwise, chars are
+	// treated as u8 and enums as int, floats are truncated towards zero when cast to integers, and anything cast to bool is compared against zero.
+	pub fn generate_cast(&mut self, child: &ASTNode, target_type: &Type) -> Result<LLVMValue> {
+		let mut val = self.ast_to_llvm(child, None)?;
+		self.ensure_rvalue(&mut val)?;
+		let to = self.get_format_from_type(target_type)?;
+
+		// An integer literal is used as the format it is cast to if it fits in it, so e.g. u64 literals above the range of int can be cast
+		let fits = matches!(&val, LLVMValue::Constant(Constant::Integer { value, .. }) if to.can_hold(*value));
+		let fitted = if fits { to.clone() } else { val.format() };
+		Self::fit_literal(&mut val, &fitted)?;
+		let from = val.format();
+
+		// Literals are still converted, so that they have the format they were cast to from then on
+		if from == to && !matches!(val, LLVMValue::Constant(_)) {
+			return Ok(val);
+		}
+
+		let op = match (from.as_integer(), to.as_integer()) {
+			(RegisterFormat::Integer { signed, width: from_width }, RegisterFormat::Integer { width: to_width, .. }) => match from_width.cmp(&to_width) {
+				Ordering::Less if signed => "sext",
+				Ordering::Less => "zext",
+				Ordering::Greater => "trunc",
+				Ordering::Equal => "bitcast",
+			},
+			(RegisterFormat::Float { width: from_width }, RegisterFormat::Float { width: to_width }) => match from_width.cmp(&to_width) {
+				Ordering::Less => "fpext",
+				Ordering::Greater => "fptrunc",
+				Ordering::Equal => "bitcast",
+			},
+			(RegisterFormat::Integer { signed: true, .. }, RegisterFormat::Float { .. }) => "sitofp",
+			(RegisterFormat::Integer { .. }, RegisterFormat::Float { .. }) => "uitofp",
+			(RegisterFormat::Float { .. }, RegisterFormat::Integer { signed: true, .. }) => "fptosi",
+			(RegisterFormat::Float { .. }, RegisterFormat::Integer { .. }) => "fptoui",
+			(RegisterFormat::Boolean, RegisterFormat::Integer { .. }) => "zext",
+			(RegisterFormat::Boolean, RegisterFormat::Boolean) => "bitcast",
+			(RegisterFormat::Pointer { .. }, RegisterFormat::Integer { .. }) => "ptrtoint",
+			(RegisterFormat::Integer { .. }, RegisterFormat::Pointer { .. }) => "inttoptr",
+			(RegisterFormat::Pointer { .. }, RegisterFormat::Pointer { .. }) => "bitcast",
+			(RegisterFormat::Pointer { .. }, RegisterFormat::Boolean) => {
+				self.coerce(&mut val, to)?;
+				return Ok(val);
+			},
+			(RegisterFormat::Integer { .. } | RegisterFormat::Float { .. }, RegisterFormat::Boolean) => {
+				let reg = self.update_virtual_register(1);
+				let zero = match from {
+					RegisterFormat::Float { .. } => LLVMValue::Constant(Constant::Float { value: 0.0, format: from }),
+					RegisterFormat::Char => LLVMValue::Constant(Constant::Char(0)),
+					_ => LLVMValue::Constant(Constant::Integer { value: 0, format: from }),
+				};
+				self.writer.write_cmp(&val, &zero, reg, Token::ExclamationEqual.get_pnemonic(&val.format()))?;
+				return Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), to, true)));
+			},
+			_ => return Err(Error::BadConversion { from, to }),
+		};
+
+		let reg = self.update_virtual_register(1);
+		self.writer.write_conversion(op, &val, reg, &to)?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), to, true)))
+	}
+
+	// Generate && or ||, only evaluating the right operand if the left one doesn't decide the result.
+	// Each operand ends in a block of its own so the phi knows which block each value comes from.
+	pub fn generate_logical(&mut self, token: &Token, left: &ASTNode, right: &ASTNode) -> Result<LLVMValue> {
+		let left_label = Label::new(self.update_label_count(1));
+		let right_label = Label::new(self.update_label_count(1));
+		let right_end_label = Label::new(self.update_label_count(1));
+		let tail_label = Label::new(self.update_label_count(1));
+
+		let mut left_llvm = self.ast_to_llvm(left, None)?;
+		self.ensure_rvalue(&mut left_llvm)?;
+		self.coerce(&mut left_llvm, RegisterFormat::Boolean).map_err(|error| error.at(left.span()))?;
+		self.writer.write_branch(&left_label)?;
+		self.writer.write_label(&left_label)?;
+
+		// && skips the right operand when the left is false, || when it is true
+		let short_circuit = matches!(token, Token::Pipe2);
+		if short_circuit {
+			self.writer.write_cond_branch(&left_llvm, &tail_label, &right_label)?;
+		} else {
+			self.writer.write_cond_branch(&left_llvm, &right_label, &tail_label)?;
+		}
+
+		self.writer.write_label(&right_label)?;
+		let mut right_llvm = self.ast_to_llvm(right, None)?;
+		self.ensure_rvalue(&mut right_llvm)?;
+		self.coerce(&mut right_llvm, RegisterFormat::Boolean).map_err(|error| error.at(right.span()))?;
+		self.writer.write_branch(&right_end_label)?;
+		self.writer.write_label(&right_end_label)?;
+		self.writer.write_branch(&tail_label)?;
+
+		self.writer.write_label(&tail_label)?;
+		let reg = self.update_virtual_register(1);
+		let incoming = [(LLVMValue::Constant(Constant::Boolean(short_circuit)), left_label), (right_llvm, right_end_label)];
+		self.writer.write_phi(reg, &RegisterFormat::Boolean, &incoming)?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true)))
+	}
+
 	// Generate LLVMValue for multiplication
 	pub fn generate_mul(&mut self, mut left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
 		self.ensure_rvalue(&mut left)?;
@@ -155,7 +636,7 @@ impl Generator {
 		let reg = self.update_virtual_register(1);
 		self.writer.write_mul(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
 	}
 
 	// Generate LLVMValue for subtraction
@@ -166,7 +647,7 @@ impl Generator {
 		let reg = self.update_virtual_register(1);
 		self.writer.write_sub(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
 	}
 
 	// Generate LLVMValue for addition
@@ -177,7 +658,7 @@ impl Generator {
 		let reg = self.update_virtual_register(1);
 		self.writer.write_add(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
 	}
 
 	// Generate LLVMValue for division
@@ -186,7 +667,32 @@ impl Generator {
 		let reg = self.update_virtual_register(1);
 		self.writer.write_div(&left, &right, reg)?;
 
-		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Integer, true)))
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
+	}
+
+	// Generate LLVMValue for remainder
+	pub fn generate_rem(&mut self, mut left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
+		self.ensure_integer_operands(&mut left, &mut right)?;
+		let reg = self.update_virtual_register(1);
+		self.writer.write_rem(&left, &right, reg)?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
+	}
+
+	// Generate LLVMValue for a bitwise or shift operator
+	pub fn generate_bitwise(&mut self, token: &Token, mut left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
+		self.ensure_integer_operands(&mut left, &mut right)?;
+		let reg = self.update_virtual_register(1);
+		match token {
+			Token::Ampersand => self.writer.write_and(&left, &right, reg),
+			Token::Pipe => self.writer.write_or(&left, &right, reg),
+			Token::Caret => self.writer.write_xor(&left, &right, reg),
+			Token::LessThan2 => self.writer.write_shl(&left, &right, reg),
+			Token::GreaterThan2 => self.writer.write_shr(&left, &right, reg),
+			_ => Err(Error::BinaryOperatorExpected { received: token.clone() }),
+		}?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), left.format(), true)))
 	}
 
 	// Generate LLVMValue for assignment of left = right
@@ -197,11 +703,11 @@ impl Generator {
 		self.ensure_lvalue(&mut new_left)?;
 
 		// Special case: left format is pointer, so check if pointee matches right
-		if let RegisterFormat::Pointer { pointee } = left.format() {
-			pointee.expect(right.format())?;
-		} else {
-			left.format().expect(right.format())?;
-		}
+		let expected = match left.format() {
+			RegisterFormat::Pointer { pointee } => *pointee,
+			fmt => fmt,
+		};
+		self.check_assignment(&mut right, &expected)?;
 		self.writer.write_store(&right, &new_left)?;
 		Ok(left)
 	}
@@ -210,31 +716,45 @@ impl Generator {
 	pub fn generate_comparison(&mut self, operator: Token, mut left: LLVMValue, mut right: LLVMValue) -> Result<LLVMValue> {
 		// Make sure both sides are operands, compare them, and store the result as a boolean register
 		self.ensure_comparison_operands(&mut left, &mut right, &operator)?;
-		let pnemonic = operator.get_pnemonic();
+		let pnemonic = operator.get_pnemonic(&left.format());
 		let reg = self.update_virtual_register(1);
 		self.writer.write_cmp(&left, &right, reg, pnemonic)?;
 		
 		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true)))
 	}
 
-	pub fn generate_let(&mut self, name: &String, val_type: &Option<Type>, value: &Option<Box<ASTNode>>) -> Result<LLVMValue> {
-		if let Ok(_) = self.local_symbol_table.get(name) {
-			return Err(Error::SymbolDeclared { name: name.to_owned() });
+	pub fn generate_let(&mut self, name: &String, name_span: &Span, val_type: &Option<Type>, type_span: &Option<Span>, value: &Option<Box<ASTNode>>, span: &Span) -> Result<LLVMValue> {
+		if let Ok(declared) = self.local_symbol_table.get(name) {
+			return Err(Error::SymbolDeclared { name: name.to_owned() }.at(name_span).with_note(declared.span(), "previously declared here"));
 		}
 
 		if let Some(val) = value {
-			let mut assigned_llvm = self.ast_to_llvm(&val, None)?;
+			let declared_fmt = val_type.as_ref().map(|v| self.get_format_from_type(v)).transpose()?;
+			let assigned = match &declared_fmt {
+				Some(format) => self.generate_expecting(val, format),
+				None => self.ast_to_llvm(val, None),
+			};
+			let mut assigned_llvm = match assigned {
+				Ok(assigned_llvm) => assigned_llvm,
+				Err(error) => {
+					self.declare_after_error(name, val_type, name_span);
+					return Err(error);
+				}
+			};
 			self.ensure_rvalue(&mut assigned_llvm)?;
 			// If val_type is not given, use implicit format
-			let reg_fmt = match val_type {
-				Some(v) => self.get_format_from_type(v)?,
-				None => assigned_llvm.format(),
-			};
+			let reg_fmt = declared_fmt.unwrap_or_else(|| assigned_llvm.format());
 			
-			if !assigned_llvm.format().can_convert_to(&reg_fmt) {
-				Err(Error::InvalidAssignment { received: assigned_llvm.format().to_owned(), expected: reg_fmt.clone() })?;
+			if let Err(error) = self.check_assignment(&mut assigned_llvm, &reg_fmt) {
+				let mut error = error.at(val.span());
+				if let Some(type_span) = type_span {
+					error = error.with_note(type_span, &format!("'{name}' is declared as {reg_fmt} here"));
+				}
+
+				self.declare_after_error(name, val_type, name_span);
+				return Err(error);
 			}
-			let (symbol, reg) = self.local_symbol_table.create_local(name, &reg_fmt);
+			let (symbol, reg) = self.local_symbol_table.create_local(name, &reg_fmt, name_span);
 			self.writer.write_local_alloc(&reg, &reg_fmt)?;
 			self.writer.write_store(&assigned_llvm, &LLVMValue::VirtualRegister(reg))?;
 			self.local_symbol_table.insert(symbol);
@@ -242,18 +762,42 @@ impl Generator {
 			// No value assigned; if value type specified, assign that type; else, assign an int
 			let reg_fmt = match val_type {
 				Some(v) => self.get_format_from_type(v)?,
-				None => RegisterFormat::Integer
+				None => {
+					self.warnings.push(Warning::new(Lint::ImplicitInt, format!("'{name}' has no type or initial value, so it defaults to int"), span.clone()));
+					RegisterFormat::INT
+				},
 			};
-			let (symbol, reg) = self.local_symbol_table.create_local(name, &reg_fmt);
+			let (symbol, reg) = self.local_symbol_table.create_local(name, &reg_fmt, name_span);
 			self.writer.write_local_alloc(&reg, &reg_fmt)?;
 			self.local_symbol_table.insert(symbol);
 		}
+		self.local_declarations.push(Declaration::new(name, name_span, Lint::UnusedVariables));
 
 		Ok(LLVMValue::None)
 	}
 
+	// Verify that value can be stored in a local of the expected format. Values are stored as they are,
+	// so unlike coerce this doesn't allow conversions such as pointer to bool; only literals take on the format.
+	pub fn check_assignment(&self, value: &mut LLVMValue, expected: &RegisterFormat) -> Result<()> {
+		Self::fit_literal(value, expected)?;
+		if value.format() == *expected {
+			Ok(())
+		} else {
+			Err(Error::InvalidAssignment { received: value.format(), expected: expected.clone() })
+		}
+	}
+
+	// Declare a local whose initializer had an error, so that later uses of it aren't reported as well.
+	// Nothing is emitted as the output is thrown away.
+	fn declare_after_error(&mut self, name: &String, val_type: &Option<Type>, span: &Span) {
+		if let Some(Ok(reg_fmt)) = val_type.as_ref().map(|v| self.get_format_from_type(v)) {
+			let (symbol, _reg) = self.local_symbol_table.create_local(name, &reg_fmt, span);
+			self.local_symbol_table.insert(symbol);
+		}
+	}
+
 	// Generate if statement
-	pub fn generate_if(&mut self, expr: &ASTNode, block: &Vec<ASTNode>, else_block: &Option<Vec<ASTNode>>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
+	pub fn generate_if(&mut self, expr: &ASTNode, block: &[ASTNode], else_block: &Option<Vec<ASTNode>>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
 		let mut expr_llvm = self.ast_to_llvm(expr, None)?;
 		self.ensure_rvalue(&mut expr_llvm)?;
 		self.coerce(&mut expr_llvm, RegisterFormat::Boolean)?;
@@ -271,17 +815,13 @@ impl Generator {
 			// Write body portion of if statement
 			self.writer.write_label(&body_label)?;
 
-			for block_statement in block {
-				self.ast_to_llvm(block_statement, expected_fmt.to_owned())?;
-			}
+			self.generate_block(block, expected_fmt);
 
 			self.writer.write_branch(&tail_label)?;
 			
 			// Write else portion
 			self.writer.write_label(&else_label)?;
-			for else_statement in else_block {
-				self.ast_to_llvm(else_statement, expected_fmt.to_owned())?;
-			}
+			self.generate_block(else_block, expected_fmt);
 
 			self.writer.write_branch(&tail_label)?;
 			self.writer.write_label(&tail_label)?;
@@ -294,9 +834,7 @@ impl Generator {
 			// Body portion
 			self.writer.write_label(&body_label)?;
 
-			for block_statement in block {
-				self.ast_to_llvm(block_statement, None)?;
-			}
+			self.generate_block(block, expected_fmt);
 
 			self.writer.write_branch(&tail_label)?;
 			self.writer.write_label(&tail_label)?;
@@ -305,7 +843,7 @@ impl Generator {
 		Ok(LLVMValue::None)
 	}
 
-	pub fn generate_while(&mut self, expr: &ASTNode, block: &Vec<ASTNode>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
+	pub fn generate_while(&mut self, expr: &ASTNode, block: &[ASTNode], expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
 		let cond_label = Label::new(self.update_label_count(1));
 		let body_label = Label::new(self.update_label_count(1));
 		let tail_label = Label::new(self.update_label_count(1));
@@ -314,14 +852,12 @@ impl Generator {
 		self.writer.write_label(&cond_label)?;
 		let mut expr_llvm = self.ast_to_llvm(expr, None)?;
 		self.ensure_rvalue(&mut expr_llvm)?;
-		expr_llvm.format().expect(RegisterFormat::Boolean)?;
+		RegisterFormat::Boolean.expect(expr_llvm.format()).map_err(|error| error.at(expr.span()))?;
 		self.writer.write_cond_branch(&expr_llvm, &body_label, &tail_label)?;
 
 		// Write body
 		self.writer.write_label(&body_label)?;
-		for body_statement in block {
-			self.ast_to_llvm(body_statement, expected_fmt.to_owned())?;
-		}
+		self.generate_block(block, expected_fmt);
 		self.writer.write_branch(&cond_label)?;
 
 		// Tail
@@ -331,34 +867,38 @@ impl Generator {
 	}
 
 	// Generate a function, including header and body
-	pub fn generate_function(&mut self, name: String, parameters: &Vec<FunctionParameter>, body_block: &Vec<ASTNode>, return_type: &Type) -> Result<LLVMValue> {
-		let return_fmt = self.get_format_from_type(return_type)?;
+	pub fn generate_function(&mut self, name: String, parameters: &[FunctionParameter], body_block: &[ASTNode], return_type: &Type, signature_span: &Span) -> Result<LLVMValue> {
+		let return_fmt = self.get_format_from_type(return_type).map_err(|error| error.at(signature_span))?;
 		let mut param_values: Vec<LLVMValue> = Vec::new();
-		for (_, param) in parameters.iter().enumerate() {
-			param_values.push(LLVMValue::VirtualRegister(VirtualRegister::new(param.name.to_owned(), self.get_format_from_type(&param.param_type)?, true)));
+		for param in parameters.iter() {
+			let param_fmt = self.get_format_from_type(&param.param_type).map_err(|error| error.at(&param.span))?;
+			param_values.push(LLVMValue::VirtualRegister(VirtualRegister::new(param.name.to_owned(), param_fmt, true)));
 		}
 
-		let signature = FunctionSignature::new(&param_values.iter().map(|p| p.format()).collect(), return_fmt.clone());
+		let signature = FunctionSignature::new(&param_values.iter().map(|p| p.format()).collect::<Vec<RegisterFormat>>(), return_fmt.clone());
 
 		// Write function header, convert args into locals, generate the block statements, and close function definition
 		self.writer.write_function_header(&name, &param_values, &return_fmt)?;
 
 		for (i, param) in param_values.iter().enumerate() {
 			let arg_reg = VirtualRegister::new("arg.".to_owned() + &i.to_string(), param.format(), true);
-			let (local_symbol, local_reg) = self.local_symbol_table.create_local(&parameters[i].name, &param.format());
+			let (local_symbol, local_reg) = self.local_symbol_table.create_local(&parameters[i].name, &param.format(), &parameters[i].span);
 
 			self.writer.write_local_alloc(&local_reg, &param.format())?;
-			self.writer.write_store(&LLVMValue::VirtualRegister(arg_reg), &LLVMValue::VirtualRegister(VirtualRegister::new(parameters[i].name.to_owned(), local_symbol.value().format().to_pointer(), true)))?;
+			self.writer.write_store(&LLVMValue::VirtualRegister(arg_reg), &LLVMValue::VirtualRegister(local_reg))?;
 			self.local_symbol_table.insert(local_symbol);
+			self.local_declarations.push(Declaration::new(&parameters[i].name, &parameters[i].span, Lint::UnusedParameters));
 		}
 
 		// Add to symbol table before parsing body so recursive functions can exist
-		let (func_symbol, _func_register) = self.global_symbol_table.create_function(&name, &signature);
+		let (func_symbol, _func_register) = self.global_symbol_table.create_function(&name, &signature, signature_span);
 		self.global_symbol_table.insert(func_symbol);
+		self.function_declarations.push(Declaration::new(&name, signature_span, Lint::UnusedFunctions));
 
-		for block_statement in body_block {
-			self.ast_to_llvm(block_statement, Some(return_fmt.clone()))?;
-		}
+		self.generate_block(body_block, &Some(return_fmt.clone()));
+
+		let local_declarations = std::mem::take(&mut self.local_declarations);
+		self.warn_unused(local_declarations.into_iter());
 
 		self.writer.write_function_close()?;
 		self.free_register_count = 0;
@@ -370,12 +910,14 @@ impl Generator {
 
 	// Generate a return statement
 	pub fn generate_return(&mut self, expr: &Option<Box<ASTNode>>, expected_fmt: &Option<RegisterFormat>) -> Result<LLVMValue> {
-		let mut val = match expr {
-			Some(expr) => self.ast_to_llvm(expr, None)?,
-			None => LLVMValue::VirtualRegister(VirtualRegister::new(self.update_virtual_register(0).to_string(), RegisterFormat::Void, true)),
+		let mut val = match (expr, expected_fmt) {
+			(Some(expr), Some(fmt)) => self.generate_expecting(expr, fmt)?,
+			(Some(expr), None) => self.ast_to_llvm(expr, None)?,
+			(None, _) => LLVMValue::VirtualRegister(VirtualRegister::new(self.update_virtual_register(0).to_string(), RegisterFormat::Void, true)),
 		};
 		self.ensure_rvalue(&mut val)?;
 		if let Some(fmt) = expected_fmt {
+			Self::fit_literal(&mut val, fmt)?;
 			fmt.expect(val.format())?;
 		}
 
@@ -390,26 +932,37 @@ impl Generator {
 	}
 
 	// Generate a function call given name and args
-	pub fn generate_function_call(&mut self, name: &String, args: &Vec<ASTNode>) -> Result<LLVMValue> {
-		// Parse arguments
+	pub fn generate_function_call(&mut self, name: &str, args: &[ASTNode]) -> Result<LLVMValue> {
+		// Parse arguments; those made only of literals are folded as the format of their parameter
+		let params = match self.global_symbol_table.get(name).map(|symbol| symbol.value().format()) {
+			Ok(RegisterFormat::Function { signature }) => signature.params().clone(),
+			_ => Vec::new(),
+		};
 		let mut arg_vals: Vec<LLVMValue> = Vec::new();
-		for node in args {
-			arg_vals.push(self.ast_to_llvm(node, None)?);
+		for (i, node) in args.iter().enumerate() {
+			arg_vals.push(match params.get(i) {
+				Some(fmt) => self.generate_expecting(node, fmt)?,
+				None => self.ast_to_llvm(node, None)?,
+			});
 		}
 
-		for (_, arg) in arg_vals.iter_mut().enumerate() {
+		for arg in arg_vals.iter_mut() {
 			self.ensure_rvalue(arg)?;
 		}
 
 		// Get function symbol and check that args match
 		let func_symbol = self.global_symbol_table.get(name)?.clone();
-		if let Symbol::Function { name, value } = func_symbol {
+		Self::mark_used(&mut self.function_declarations, name);
+		if let Symbol::Function { name, value, span } = func_symbol {
 			// existing function call, check arg types
 			if let RegisterFormat::Function { signature } = value.format() {
 				// Guaranteed if symbol is function
 				for (i, fmt) in signature.params().iter().enumerate() {
+					if let Some(arg) = arg_vals.get_mut(i) {
+						Self::fit_literal(arg, fmt)?;
+					}
 					if !fmt.can_convert_to(&arg_vals.get(i).map_or(RegisterFormat::Void, |res| res.format())) {
-						return Err(Error::ArgumentMismatch { expected: signature, received: arg_vals })
+						return Err(Error::ArgumentMismatch { expected: signature, received: arg_vals }.with_note(&span, &format!("'{name}' is declared here")))
 					}
 				}
 
@@ -419,13 +972,13 @@ impl Generator {
 
 				self.writer.write_function_call(&name, &arg_vals, &ret_reg)?;
 
-				return Ok(ret_reg)
+				Ok(ret_reg)
 			} else {
 				// this is just for Rust
-				return Ok(LLVMValue::None)
+				Ok(LLVMValue::None)
 			}
 		} else {
-			return Err(Error::ExpressionExpected)
+			Err(Error::ExpressionExpected)
 		}
 	}
 
@@ -438,12 +991,297 @@ impl Generator {
 			return Err(Error::ExpressionExpected)
 		}
 
+		let format = val.format();
+		Self::fit_literal(&mut val, &format).map_err(|error| error.at(expr.span()))?;
+		if let RegisterFormat::Array { .. } | RegisterFormat::Struct { .. } = format {
+			return Err(Error::Unprintable { received: format });
+		}
+		let val = self.widen_vararg(val)?;
+
+		self.update_virtual_register(1);
+		self.writer.write_print(&val, &format)?;
+
+		Ok(LLVMValue::None)
+	}
+
+	// Generate a print with a format string, which is lowered to a single printf call.
+	// Each {} is replaced by the conversion for its argument's format; bools are printed as true or false.
+	pub fn generate_formatted_print(&mut self, pieces: &[Vec<u8>], args: &[ASTNode]) -> Result<LLVMValue> {
+		if args.len() != pieces.len() - 1 {
+			return Err(Error::FormatArgumentMismatch { expected: pieces.len() - 1, received: args.len() });
+		}
+
+		// % in the text must not be taken as a conversion
+		let escape = |piece: &[u8]| piece.iter().flat_map(|&byte| if byte == b'%' { vec![b'%', b'%'] } else { vec![byte] }).collect::<Vec<u8>>();
+		let mut fstring = escape(&pieces[0]);
+		let mut arg_vals = Vec::new();
+		for (arg, piece) in args.iter().zip(&pieces[1..]) {
+			let mut val = self.ast_to_llvm(arg, None)?;
+			self.ensure_rvalue(&mut val)?;
+			let format = val.format();
+			Self::fit_literal(&mut val, &format).map_err(|error| error.at(arg.span()))?;
+
+			let conversion = match val.format() {
+				RegisterFormat::Integer { signed: true, .. } | RegisterFormat::Enum { .. } => "%lld",
+				RegisterFormat::Integer { signed: false, .. } => "%llu",
+				RegisterFormat::Float { .. } => "%f",
+				RegisterFormat::Char => "%c",
+				RegisterFormat::Boolean => {
+					let (true_str, false_str) = (self.intern_string(b"true"), self.intern_string(b"false"));
+					let reg = self.update_virtual_register(1);
+					self.writer.write_select(reg, &val, &true_str, &false_str)?;
+					val = LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Char.to_pointer(), true));
+					"%s"
+				},
+				RegisterFormat::Pointer { pointee } if *pointee == RegisterFormat::Char => "%s",
+				RegisterFormat::Pointer { .. } | RegisterFormat::Null => "%p",
+				RegisterFormat::Void => return Err(Error::ExpressionExpected.at(arg.span())),
+				format => return Err(Error::Unprintable { received: format }.at(arg.span())),
+			};
+
+			fstring.extend_from_slice(conversion.as_bytes());
+			fstring.extend(escape(piece));
+			arg_vals.push(self.widen_vararg(val)?);
+		}
+
+		let fstring = self.intern_string(&fstring);
 		self.update_virtual_register(1);
-		self.writer.write_print(&val)?;
+		self.writer.write_printf(&fstring, &arg_vals)?;
 
 		Ok(LLVMValue::None)
 	}
 
+	// Convert a value to what a variadic function is given in its place: every integer as 64 bits,
+	// every float as a double, and chars as an int, which is what %c takes.
+	fn widen_vararg(&mut self, val: LLVMValue) -> Result<LLVMValue> {
+		let widened = match val.format() {
+			RegisterFormat::Integer { signed, width: ..64 } => Some((if signed { "sext" } else { "zext" }, RegisterFormat::Integer { signed, width: 64 })),
+			RegisterFormat::Boolean => Some(("zext", RegisterFormat::INT)),
+			RegisterFormat::Float { width: 32 } => Some(("fpext", RegisterFormat::F64)),
+			RegisterFormat::Char => Some(("zext", RegisterFormat::Integer { signed: true, width: 32 })),
+			_ => None,
+		};
+		let Some((op, wide)) = widened else {
+			return Ok(val);
+		};
+
+		let reg = self.update_virtual_register(1);
+		self.writer.write_conversion(op, &val, reg, &wide)?;
+
+		Ok(LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), wide, true)))
+	}
+
+	// Generate [a, b, c]. Elements take the format of the first one that isn't a literal, like the operands of a binary operator.
+	pub fn generate_array_literal(&mut self, elements: &[ASTNode], expected_element: Option<&RegisterFormat>) -> Result<LLVMValue> {
+		let mut values = Vec::new();
+		for element in elements {
+			let mut value = match expected_element {
+				Some(format) => self.generate_expecting(element, format)?,
+				None => self.ast_to_llvm(element, None)?,
+			};
+			self.ensure_rvalue(&mut value)?;
+			values.push(value);
+		}
+
+		let element_fmt = values.iter()
+			.find(|value| !matches!(value, LLVMValue::Constant(_)))
+			.unwrap_or(&values[0])
+			.format();
+		for (value, element) in values.iter_mut().zip(elements) {
+			self.check_assignment(value, &element_fmt).map_err(|error| error.at(element.span()))?;
+		}
+
+		self.build_array(element_fmt, values)
+	}
+
+	// Generate [value; count], which evaluates value once
+	pub fn generate_array_repeat(&mut self, value: &ASTNode, count: usize, expected_element: Option<&RegisterFormat>) -> Result<LLVMValue> {
+		let mut value = match expected_element {
+			Some(format) => self.generate_expecting(value, format)?,
+			None => self.ast_to_llvm(value, None)?,
+		};
+		self.ensure_rvalue(&mut value)?;
+
+		self.build_array(value.format(), vec![value; count])
+	}
+
+	// An array of values that all have element_fmt
+	fn build_array(&mut self, element_fmt: RegisterFormat, values: Vec<LLVMValue>) -> Result<LLVMValue> {
+		if matches!(element_fmt, RegisterFormat::Void) {
+			return Err(Error::ExpressionExpected);
+		}
+
+		let format = RegisterFormat::Array { element: Box::new(element_fmt), len: values.len() };
+		self.build_aggregate(format, values)
+	}
+
+	// An array or struct of values that already have the formats of its elements or fields.
+	// Aggregates of constants are constants themselves; others are built up an element at a time.
+	fn build_aggregate(&mut self, format: RegisterFormat, values: Vec<LLVMValue>) -> Result<LLVMValue> {
+		let constants: Option<Vec<Constant>> = values.iter().map(|value| match value {
+			LLVMValue::Constant(constant) => Some(constant.clone()),
+			_ => None,
+		}).collect();
+		if let Some(elements) = constants {
+			return Ok(LLVMValue::Constant(match format {
+				RegisterFormat::Struct { .. } => Constant::Struct { fields: elements, format },
+				_ => Constant::Array { elements, format },
+			}));
+		}
+
+		let mut aggregate = LLVMValue::Constant(Constant::Undefined(format.clone()));
+		for (index, value) in values.iter().enumerate() {
+			let reg = self.update_virtual_register(1);
+			self.writer.write_insert_value(reg, &aggregate, value, index)?;
+			aggregate = LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), format.clone(), true));
+		}
+
+		Ok(aggregate)
+	}
+
+	// Generate Point { x: 1, y: 2 }. Fields can be given in any order, but each exactly once; they are evaluated in the order they are given.
+	pub fn generate_struct_literal(&mut self, name: &str, fields: &[FieldInitializer]) -> Result<LLVMValue> {
+		let layout = self.get_struct(name)?.clone();
+		let mut values: Vec<Option<LLVMValue>> = vec![None; layout.fields().len()];
+		for field in fields {
+			let (index, format) = layout.field(&field.name).map_err(|error| error.at(&field.span))?;
+			if values[index].is_some() {
+				return Err(Error::DuplicateField { name: name.to_owned(), field: field.name.to_owned() }.at(&field.span));
+			}
+
+			let mut value = self.generate_expecting(&field.value, format)?;
+			self.ensure_rvalue(&mut value)?;
+			self.check_assignment(&mut value, format).map_err(|error| error.at(field.value.span()))?;
+			values[index] = Some(value);
+		}
+
+		let missing: Vec<String> = layout.fields().iter().zip(&values)
+			.filter(|(_, value)| value.is_none())
+			.map(|((field, _), _)| field.to_owned())
+			.collect();
+		if !missing.is_empty() {
+			return Err(Error::MissingFields { name: name.to_owned(), fields: missing });
+		}
+
+		self.build_aggregate(layout.format(), values.into_iter().flatten().collect())
+	}
+
+	// Generate struct.field as an lvalue. A pointer to a struct is dereferenced first, so p.x is (*p).x.
+	pub fn generate_field(&mut self, child: &ASTNode, field: &str) -> Result<LLVMValue> {
+		let struct_ptr = match self.ast_to_llvm(child, None)? {
+			LLVMValue::Indirect { pointee, referenced_fmt: RegisterFormat::Struct { .. } } => *pointee,
+			mut value => {
+				self.ensure_rvalue(&mut value)?;
+				match value.format() {
+					RegisterFormat::Pointer { pointee } if matches!(*pointee, RegisterFormat::Struct { .. }) => value,
+					RegisterFormat::Struct { .. } => self.store_temporary(&value)?,
+					format => return Err(Error::NotAStruct { received: format }),
+				}
+			},
+		};
+
+		let RegisterFormat::Pointer { pointee } = struct_ptr.format() else {
+			return Err(Error::NotAStruct { received: struct_ptr.format() });
+		};
+		let RegisterFormat::Struct { name } = *pointee else {
+			return Err(Error::NotAStruct { received: *pointee });
+		};
+		let (index, format) = self.get_struct(&name)?.field(field).map(|(index, format)| (index, format.clone()))?;
+
+		let reg = self.update_virtual_register(1);
+		self.writer.write_field_pointer(reg, &struct_ptr, index)?;
+		let field_ptr = VirtualRegister::new(reg.to_string(), format.to_pointer(), true);
+
+		Ok(LLVMValue::Indirect { pointee: Box::new(LLVMValue::VirtualRegister(field_ptr)), referenced_fmt: format })
+	}
+
+	// Generate Enum::Variant, which is a constant of the enum's format
+	pub fn generate_path(&mut self, type_name: &str, member: &str) -> Result<LLVMValue> {
+		let layout = self.get_enum(type_name)?;
+		let value = layout.variant(member)?;
+
+		Ok(LLVMValue::Constant(Constant::Integer { value: value.into(), format: layout.format() }))
+	}
+
+	// Store a value that isn't stored anywhere, e.g. one returned by a call, in a temporary and give its address
+	fn store_temporary(&mut self, value: &LLVMValue) -> Result<LLVMValue> {
+		let reg = self.update_virtual_register(1);
+		let temporary = VirtualRegister::new(reg.to_string(), value.format().to_pointer(), true);
+		self.writer.write_local_alloc(&temporary, &value.format())?;
+		let temporary = LLVMValue::VirtualRegister(temporary);
+		self.writer.write_store(value, &temporary)?;
+
+		Ok(temporary)
+	}
+
+	// Generate array[index] as an lvalue, so it can be loaded, stored to and referenced like a local.
+	// Constant indexes must be in bounds; others are checked at runtime if bounds checks are on.
+	pub fn generate_index(&mut self, array: &ASTNode, index: &ASTNode) -> Result<LLVMValue> {
+		let array = self.ast_to_llvm(array, None)?;
+		let (array_ptr, element, len) = match array {
+			LLVMValue::Indirect { pointee, referenced_fmt: RegisterFormat::Array { element, len } } => (*pointee, element, len),
+			LLVMValue::Indirect { referenced_fmt, .. } => return Err(Error::NotIndexable { received: referenced_fmt }),
+			array => {
+				let RegisterFormat::Array { element, len } = array.format() else {
+					return Err(Error::NotIndexable { received: array.format() });
+				};
+
+				(self.store_temporary(&array)?, element, len)
+			},
+		};
+
+		let mut index_llvm = self.ast_to_llvm(index, None)?;
+		self.ensure_rvalue(&mut index_llvm)?;
+		// Indexes are widened to 64 bits so they can be compared with the length; getelementptr
+		// would sign extend narrower ones itself, which makes large unsigned ones negative
+		let index_llvm = match index_llvm.format() {
+			RegisterFormat::Integer { signed, width: ..64 } => {
+				let reg = self.update_virtual_register(1);
+				let wide = RegisterFormat::Integer { signed, width: 64 };
+				self.writer.write_conversion(if signed { "sext" } else { "zext" }, &index_llvm, reg, &wide)?;
+				LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), wide, true))
+			},
+			RegisterFormat::Integer { .. } => index_llvm,
+			format => return Err(Error::IndexNotInteger { received: format }.at(index.span())),
+		};
+
+		match index_llvm {
+			LLVMValue::Constant(Constant::Integer { value, .. }) if value < 0 || value >= len as i128 => {
+				return Err(Error::IndexOutOfBounds { len, index: value }.at(index.span()));
+			},
+			LLVMValue::Constant(_) => {},
+			_ if self.bounds_checks => self.generate_bounds_check(&index_llvm, len, index.span())?,
+			_ => {},
+		}
+
+		let reg = self.update_virtual_register(1);
+		self.writer.write_element_pointer(reg, &array_ptr, &index_llvm)?;
+		let element_ptr = VirtualRegister::new(reg.to_string(), element.to_pointer(), true);
+
+		Ok(LLVMValue::Indirect { pointee: Box::new(LLVMValue::VirtualRegister(element_ptr)), referenced_fmt: *element })
+	}
+
+	// Generate a check that a 64 bit index is less than len, which aborts with a message giving the index's location if it isn't.
+	// Negative indexes are taken as unsigned, so they fail the same check.
+	fn generate_bounds_check(&mut self, index: &LLVMValue, len: usize, span: &Span) -> Result<()> {
+		let in_bounds_label = Label::new(self.update_label_count(1));
+		let out_of_bounds_label = Label::new(self.update_label_count(1));
+
+		let reg = self.update_virtual_register(1);
+		self.writer.write_cmp(index, &LLVMValue::Constant(Constant::int(len as i128)), reg, String::from("ult"))?;
+		let in_bounds = LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true));
+		self.writer.write_cond_branch(&in_bounds, &in_bounds_label, &out_of_bounds_label)?;
+
+		self.writer.write_label(&out_of_bounds_label)?;
+		let location = span.to_string().replace('%', "%%");
+		let conversion = if index.format().is_signed() { "%lld" } else { "%llu" };
+		let message = self.intern_string(format!("{location}: index out of bounds: the len is {len} but the index is {conversion}\n").as_bytes());
+		self.update_virtual_register(2);
+		self.writer.write_bounds_check_failure(&message, index)?;
+
+		self.writer.write_label(&in_bounds_label)
+	}
+
 	// Generate instructions for dereferencing a node
 	pub fn generate_deref(&mut self, node: &ASTNode) -> Result<LLVMValue> {
 		// Get target to dereference
@@ -462,7 +1300,6 @@ impl Generator {
 	pub fn generate_ref(&mut self, node: &ASTNode) -> Result<LLVMValue> {
 		// Get target to reference
 		let trg = self.ast_to_llvm(node, None)?;
-		dbg!(&trg);
 		if let LLVMValue::Indirect { pointee, .. } = trg {
 			Ok(*pointee)
 		} else {
@@ -484,37 +1321,106 @@ impl Generator {
 		}
 	}
 
-	// Verify that LLVMValues are able to be operated on by arithmetic
-	pub fn ensure_arithmetic_operands(&mut self, mut left: &mut LLVMValue, mut right: &mut LLVMValue) -> Result<()> {
-		self.ensure_rvalue(&mut left)?;
-		self.ensure_rvalue(&mut right)?;
+	// Verify that LLVMValues are able to be operated on by arithmetic, i.e. are numbers of the same format
+	pub fn ensure_arithmetic_operands(&mut self, left: &mut LLVMValue, right: &mut LLVMValue) -> Result<()> {
+		self.ensure_rvalue(left)?;
+		self.ensure_rvalue(right)?;
 
-		if let RegisterFormat::Integer = left.format() {
-			if let RegisterFormat::Integer = right.format() {
-				Ok(())
-			} else {
-				Err(Error::InvalidArithmeticOperand { received: right.format() })
-			}
+		if !left.format().is_numeric() {
+			return Err(Error::InvalidArithmeticOperand { received: left.format() });
+		}
+		if !right.format().is_numeric() {
+			return Err(Error::InvalidArithmeticOperand { received: right.format() });
+		}
+
+		Self::fit_literals(left, right)?;
+		if left.format() == right.format() {
+			Ok(())
+		} else {
+			Err(Error::OperandMismatch { left: left.format(), right: right.format() })
+		}
+	}
+
+	// Verify that LLVMValues are integers of the same format, for operators that don't apply to floats
+	pub fn ensure_integer_operands(&mut self, left: &mut LLVMValue, right: &mut LLVMValue) -> Result<()> {
+		self.ensure_arithmetic_operands(left, right)?;
+
+		if left.format().is_integer() {
+			Ok(())
 		} else {
 			Err(Error::InvalidArithmeticOperand { received: left.format() })
 		}
 	}
 
 	// Verify that left and right can be compared
-	pub fn ensure_comparison_operands(&mut self, mut left: &mut LLVMValue, mut right: &mut LLVMValue, op: &Token) -> Result<()> {
-		self.ensure_rvalue(&mut left)?;
-		self.ensure_rvalue(&mut right)?;
+	pub fn ensure_comparison_operands(&mut self, left: &mut LLVMValue, right: &mut LLVMValue, op: &Token) -> Result<()> {
+		self.ensure_rvalue(left)?;
+		self.ensure_rvalue(right)?;
+		Self::fit_literals(left, right)?;
 
 		let left_fmt = left.format();
 		let right_fmt = right.format();
 
 		if left_fmt.can_compare_to(&right_fmt, op) {
 			Ok(())
+		} else if left_fmt.is_numeric() && right_fmt.is_numeric() {
+			Err(Error::OperandMismatch { left: left_fmt, right: right_fmt })
 		} else {
 			Err(Error::InvalidComparisonOperands { left: left_fmt, right: right_fmt })
 		}
 	}
 
+	// Give an integer literal the integer format it is used as, if it fits in it, and a float literal the float format it is used as.
+	// Literals that are already of the format are checked too, as they can be above the range of int.
+	// Variants of enums are integer constants too, but keep their format.
+	pub fn fit_literal(value: &mut LLVMValue, expected: &RegisterFormat) -> Result<()> {
+		match value {
+			LLVMValue::Constant(Constant::Integer { value, format }) if format.is_integer() && expected.is_integer() => {
+				if !expected.can_hold(*value) {
+					return Err(Error::LiteralOutOfRange { value: *value, format: expected.clone() });
+				}
+
+				*format = expected.clone();
+			},
+			LLVMValue::Constant(Constant::Float { format, .. }) if expected.is_float() => *format = expected.clone(),
+			LLVMValue::Constant(Constant::Array { elements, format }) => {
+				let RegisterFormat::Array { element: expected_element, len } = expected else {
+					return Ok(());
+				};
+				if *len != elements.len() {
+					return Ok(());
+				}
+
+				for element in elements.iter_mut() {
+					let mut value = LLVMValue::Constant(element.clone());
+					Self::fit_literal(&mut value, expected_element)?;
+					if let LLVMValue::Constant(fitted) = value {
+						*element = fitted;
+					}
+				}
+				if let Some(first) = elements.first() {
+					*format = RegisterFormat::Array { element: Box::new(first.format()), len: *len };
+				}
+			},
+			_ => {},
+		}
+
+		Ok(())
+	}
+
+	// Give a literal operand the format of the other operand; literals used together keep their own
+	fn fit_literals(left: &mut LLVMValue, right: &mut LLVMValue) -> Result<()> {
+		match (&left, &right) {
+			(LLVMValue::Constant(_), LLVMValue::Constant(_)) => {
+				Self::fit_literal(left, &left.format())?;
+				Self::fit_literal(right, &right.format())
+			},
+			(LLVMValue::Constant(_), _) => Self::fit_literal(left, &right.format()),
+			(_, LLVMValue::Constant(_)) => Self::fit_literal(right, &left.format()),
+			_ => Ok(()),
+		}
+	}
+
 	// Ensure LLVMValue is an L-value
 	pub fn ensure_lvalue(&mut self, node: &mut LLVMValue) -> Result<()> {
 		match node {
@@ -529,7 +1435,7 @@ impl Generator {
 	// Ensure that given LLVM Value is in operatable form
 	pub fn ensure_rvalue(&mut self, node: &mut LLVMValue) -> Result<()> {
 		match node {
-			LLVMValue::None => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::Constant(Constant::Integer(3)), received: node.clone() }),
+			LLVMValue::None => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::Constant(Constant::int(3)), received: node.clone() }),
 			LLVMValue::Indirect { pointee, referenced_fmt } => {
 				*node = self.load_numbered_register(referenced_fmt.to_owned(), (**pointee).clone())?;
 				Ok(())
@@ -553,7 +1459,7 @@ impl Generator {
 				let reg = self.update_virtual_register(1);
 				let new_val = LLVMValue::VirtualRegister(VirtualRegister::new(reg.to_string(), RegisterFormat::Boolean, true));
 
-				self.writer.write_cmp(&value, &LLVMValue::Null, reg, "ne".to_string())?;
+				self.writer.write_cmp(value, &LLVMValue::Null, reg, "ne".to_string())?;
 				*value = new_val;
 
 				Ok(())
@@ -569,8 +1475,11 @@ impl Generator {
 		let fmt = match source {
 			Type::Named { type_name } => {
 				TYPE_FORMATS.iter().find_map(|type_fmt| if type_name == type_fmt.0 { Some(type_fmt.1.clone()) } else { None }  )
+					.or_else(|| self.get_struct(type_name).ok().map(StructLayout::format))
+					.or_else(|| self.get_enum(type_name).ok().map(EnumLayout::format))
 			},
 			Type::Pointer { pointee_type } => Some(RegisterFormat::Pointer { pointee: Box::new(self.get_format_from_type(pointee_type)?)}),
+			Type::Array { element_type, len } => Some(RegisterFormat::Array { element: Box::new(self.get_format_from_type(element_type)?), len: *len }),
 			Type::Void => Some(RegisterFormat::Void),
 		};
 
@@ -1,29 +1,50 @@
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 
 use crate::error::*;
 use crate::generating::llvm::LLVMValue;
 
-use super::{Constant, Label, RegisterFormat, VirtualRegister};
+use super::{Constant, Label, RegisterFormat, StructLayout, VirtualRegister};
 
+// Writes LLVM to any target; output is buffered, so call flush or into_inner once done
 #[derive(Debug)]
-pub struct Writer {
+pub struct Writer<W: Write = File> {
 	filename: String,
-	target: File,
+	target: BufWriter<W>,
 }
 
-impl Writer {
-	pub fn new(filename: String, target: File) -> Self {
+impl Writer<File> {
+	pub fn from_filename(filename: String) -> std::io::Result<Self> {
+		File::create(&filename)
+			.map(|file| Self::new(filename.clone(), file))
+	}
+}
+
+impl Writer<Vec<u8>> {
+	// Write to memory, collecting the output with into_inner
+	pub fn in_memory(filename: String) -> Self {
+		Self::new(filename, Vec::new())
+	}
+}
+
+impl<W: Write> Writer<W> {
+	pub fn new(filename: String, target: W) -> Self {
 		Self {
 			filename,
-			target,
+			target: BufWriter::new(target),
 		}
 	}
 
-	pub fn from_filename(filename: String) -> std::io::Result<Self> {
-		File::create(&filename)
-			.map(|file| Ok(Self::new(filename.clone(), file)))
-			.map_err(|cause| cause)?
+	// Write out anything still buffered
+	pub fn flush(&mut self) -> Result<()> {
+		self.target.flush()
+			.map_err(|cause| Error::FileWriteError { cause })
+	}
+
+	// Flush and give back the target
+	pub fn into_inner(self) -> Result<W> {
+		self.target.into_inner()
+			.map_err(|error| Error::FileWriteError { cause: error.into_error() })
 	}
 
 	pub fn write_preamble(&mut self) -> Result<()> {
@@ -33,7 +54,11 @@ source_filename = \"{0}\"
 target datalayout = \"e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128\"
 target triple = \"x86_64-pc-linux-gnu\"
 
-@print_int_fstring = private unnamed_addr constant [4 x i8] c\"%d\\0A\\00\", align 1
+@print_int_fstring = private unnamed_addr constant [5 x i8] c\"%ld\\0A\\00\", align 1
+@print_uint_fstring = private unnamed_addr constant [5 x i8] c\"%lu\\0A\\00\", align 1
+@print_float_fstring = private unnamed_addr constant [4 x i8] c\"%f\\0A\\00\", align 1
+@print_char_fstring = private unnamed_addr constant [4 x i8] c\"%c\\0A\\00\", align 1
+@print_string_fstring = private unnamed_addr constant [4 x i8] c\"%s\\0A\\00\", align 1
 
 ", self.filename
 		))?;
@@ -43,20 +68,24 @@ target triple = \"x86_64-pc-linux-gnu\"
 
 	pub fn write_postamble(&mut self) -> Result<()> {
 		self.write(
-&format!("declare i32 @printf(i8*, ...) #1
-
-attributes #0 = {{ noinline nounwind optnone uwtable \"frame-pointer\"=\"all\" \"min-legal-vector-width\"=\"0\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }}
-attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }}
-
-!llvm.module.flags = !{{!0, !1, !2, !3, !4}}
-!llvm.ident = !{{!5}}
-
-!0 = !{{i32 1, !\"wchar_size\", i32 4}}
-!1 = !{{i32 7, !\"PIC Level\", i32 2}}
-!2 = !{{i32 7, !\"PIE Level\", i32 2}}
-!3 = !{{i32 7, !\"uwtable\", i32 1}}
-!4 = !{{i32 7, !\"frame-pointer\", i32 2}}
-!5 = !{{!\"ICD compiler\"}}")
+"declare i32 @printf(i8*, ...) #1
+declare i32 @dprintf(i32, i8*, ...) #1
+declare i32 @fflush(i8*) #1
+declare void @abort() #2
+
+attributes #0 = { noinline nounwind optnone uwtable \"frame-pointer\"=\"all\" \"min-legal-vector-width\"=\"0\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }
+attributes #1 = { \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"stack-protector-buffer-size\"=\"8\" \"target-cpu\"=\"x86-64\" \"target-features\"=\"+cx8,+fxsr,+mmx,+sse,+sse2,+x87\" \"tune-cpu\"=\"generic\" }
+attributes #2 = { cold noreturn nounwind }
+
+!llvm.module.flags = !{!0, !1, !2, !3, !4}
+!llvm.ident = !{!5}
+
+!0 = !{i32 1, !\"wchar_size\", i32 4}
+!1 = !{i32 7, !\"PIC Level\", i32 2}
+!2 = !{i32 7, !\"PIE Level\", i32 2}
+!3 = !{i32 7, !\"uwtable\", i32 1}
+!4 = !{i32 7, !\"frame-pointer\", i32 2}
+!5 = !{!\"ICD compiler\"}"
 		)
 	}
 
@@ -79,73 +108,121 @@ attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"sta
 	pub fn write_mul(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
 		let l_val: String = match left {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
 		let r_val: String = match right {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
-		self.writeln(&format!("\t%{reg} = mul nsw {} {l_val}, {r_val}", left.val_type()))
+		self.writeln(&format!("\t%{reg} = {}mul{} {} {l_val}, {r_val}", float_prefix(left), no_signed_wrap(left), left.val_type()))
 	}
 
 	// Write a subtraction operation to the LLVM file
 	pub fn write_sub(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
 		let l_val: String = match left {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
 		let r_val: String = match right {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
-		self.writeln(&format!("\t%{reg} = sub nsw {} {l_val}, {r_val}", left.val_type()))
+		self.writeln(&format!("\t%{reg} = {}sub{} {} {l_val}, {r_val}", float_prefix(left), no_signed_wrap(left), left.val_type()))
 	}
 
 	// Write an addition operation to the LLVM file
 	pub fn write_add(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
 		let l_val: String = match left {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
 		let r_val: String = match right {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
-		self.writeln(&format!("\t%{reg} = add nsw {} {l_val}, {r_val}", left.val_type()))
+		self.writeln(&format!("\t%{reg} = {}add{} {} {l_val}, {r_val}", float_prefix(left), no_signed_wrap(left), left.val_type()))
 	}
 
-	// Write an addition operation to the LLVM file
+	// Write a division operation to the LLVM file
 	pub fn write_div(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
 		let l_val: String = match left {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
 		let r_val: String = match right {
 			LLVMValue::VirtualRegister(l) => Ok(l.to_string()),
-			LLVMValue::Constant(Constant::Integer(x)) => Ok(x.to_string()),
-			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::Integer, true)), received: left.clone() })
+			LLVMValue::Constant(c @ (Constant::Integer { .. } | Constant::Float { .. })) => Ok(c.to_string()),
+			_ => Err(Error::UnexpectedLLVMValue { expected: LLVMValue::VirtualRegister(VirtualRegister::new("0".to_string(), RegisterFormat::INT, true)), received: left.clone() })
 		}?;
 
-		self.writeln(&format!("\t%{reg} = udiv {} {l_val}, {r_val}", left.val_type()))
+		let op = match left.format() {
+			RegisterFormat::Float { .. } => "fdiv",
+			format if format.is_signed() => "sdiv",
+			_ => "udiv",
+		};
+		self.writeln(&format!("\t%{reg} = {op} {} {l_val}, {r_val}", left.val_type()))
+	}
+
+	// Write a remainder operation to the LLVM file; a signed result has the sign of left
+	pub fn write_rem(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		let op = if left.format().is_signed() { "srem" } else { "urem" };
+		self.writeln(&format!("\t%{reg} = {op} {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a bitwise and to the LLVM file
+	pub fn write_and(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = and {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a bitwise or to the LLVM file
+	pub fn write_or(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = or {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a bitwise exclusive or to the LLVM file
+	pub fn write_xor(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = xor {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a left shift to the LLVM file
+	pub fn write_shl(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = shl {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a right shift to the LLVM file; the sign bit is shifted in for signed values and zeros otherwise
+	pub fn write_shr(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32) -> Result<()> {
+		let op = if left.format().is_signed() { "ashr" } else { "lshr" };
+		self.writeln(&format!("\t%{reg} = {op} {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a conversion such as sext or trunc of val to the given format
+	pub fn write_conversion(&mut self, op: &str, val: &LLVMValue, reg: u32, format: &RegisterFormat) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = {op} {} {val} to {}", val.val_type(), format.format_type()))
 	}
 
 	// Compare left and right via 'op'
 	pub fn write_cmp(&mut self, left: &LLVMValue, right: &LLVMValue, reg: u32, op: String) -> Result<()> {
-		self.writeln(&format!("\t%{reg} = icmp {op} {} {left}, {right}", left.val_type()))
+		let cmp = if left.format().is_float() { "fcmp" } else { "icmp" };
+		self.writeln(&format!("\t%{reg} = {cmp} {op} {} {left}, {right}", left.val_type()))
+	}
+
+	// Write a float negation
+	pub fn write_fneg(&mut self, val: &LLVMValue, reg: u32) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = fneg {} {val}", val.val_type()))
 	}
 
 	// Write given label to output
@@ -158,13 +235,19 @@ attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"sta
 		self.writeln(&format!("\tbr {cond_type} {condition}, label %{t_label}, label %{f_label}", cond_type=condition.val_type()))
 	}
 
+	// Write a phi picking a value depending on which labelled block control came from
+	pub fn write_phi(&mut self, reg: u32, format: &RegisterFormat, incoming: &[(LLVMValue, Label)]) -> Result<()> {
+		let incoming: Vec<String> = incoming.iter().map(|(val, label)| format!("[ {val}, %{label} ]")).collect();
+		self.writeln(&format!("\t%{reg} = phi {} {}", format.format_type(), incoming.join(", ")))
+	}
+
 	// Write a direct branch to a label
 	pub fn write_branch(&mut self, label: &Label) -> Result<()> {
 		self.writeln(&format!("\tbr label %{label}"))
 	}
 
 	// Write function header
-	pub fn write_function_header(&mut self, name: &str, param_values: &Vec<LLVMValue>, return_fmt: &RegisterFormat) -> Result<()> {
+	pub fn write_function_header(&mut self, name: &str, param_values: &[LLVMValue], return_fmt: &RegisterFormat) -> Result<()> {
 		self.write(&format!("define dso_local {return_type} @{name}(", return_type=return_fmt.format_type()))?;
 
 		for (i, param) in param_values.iter().enumerate() {
@@ -181,7 +264,7 @@ attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"sta
 	}
 
 	// Write function call and put res in trg;
-	pub fn write_function_call(&mut self, name: &String, arg_vals: &Vec<LLVMValue>, ret_reg: &LLVMValue) -> Result<()> {
+	pub fn write_function_call(&mut self, name: &str, arg_vals: &[LLVMValue], ret_reg: &LLVMValue) -> Result<()> {
 		self.write("\t")?;
 		if let RegisterFormat::Void = ret_reg.format() {} else {
 			self.write(&format!("{ret_reg} = "))?;
@@ -207,21 +290,91 @@ attributes #1 = {{ \"frame-pointer\"=\"all\" \"no-trapping-math\"=\"true\" \"sta
 		}
 	}
 
-	// Print integer (i32)
-	pub fn write_print(&mut self, val: &LLVMValue) -> Result<()> {
-		self.writeln(&format!("\tcall i32(i8*, ...) @printf(i8* getelementptr inbounds ([4 x i8], [4 x i8]* @print_int_fstring, i32 0, i32 0), {} {val})", val.val_type()))
+	// Print a 64 bit value, a char given as an i32, or a string; floats must already be doubles
+	pub fn write_print(&mut self, val: &LLVMValue, format: &RegisterFormat) -> Result<()> {
+		let (fstring, len) = match format {
+			RegisterFormat::Float { .. } => ("print_float_fstring", 4),
+			RegisterFormat::Char => ("print_char_fstring", 4),
+			RegisterFormat::Pointer { pointee } if **pointee == RegisterFormat::Char => ("print_string_fstring", 4),
+			RegisterFormat::Integer { signed: false, .. } => ("print_uint_fstring", 5),
+			_ => ("print_int_fstring", 5),
+		};
+		self.writeln(&format!("\tcall i32(i8*, ...) @printf(i8* getelementptr inbounds ([{len} x i8], [{len} x i8]* @{fstring}, i32 0, i32 0), {} {val})", val.val_type()))
+	}
+
+	// Get the address of an element of the array that array_ptr points to
+	pub fn write_element_pointer(&mut self, reg: u32, array_ptr: &LLVMValue, index: &LLVMValue) -> Result<()> {
+		let RegisterFormat::Pointer { pointee } = array_ptr.format() else {
+			return Err(Error::InvalidDereference { received: array_ptr.format() });
+		};
+		self.writeln(&format!("\t%{reg} = getelementptr inbounds {array_type}, {array_type}* {array_ptr}, i64 0, {} {index}", index.val_type(), array_type=pointee.format_type()))
+	}
+
+	// Address of the field with the index in the struct struct_ptr points to
+	pub fn write_field_pointer(&mut self, reg: u32, struct_ptr: &LLVMValue, index: usize) -> Result<()> {
+		let RegisterFormat::Pointer { pointee } = struct_ptr.format() else {
+			return Err(Error::InvalidDereference { received: struct_ptr.format() });
+		};
+		self.writeln(&format!("\t%{reg} = getelementptr inbounds {struct_type}, {struct_type}* {struct_ptr}, i32 0, i32 {index}", struct_type=pointee.format_type()))
+	}
+
+	// Copy of an array or struct with the element or field at index replaced by value
+	pub fn write_insert_value(&mut self, reg: u32, array: &LLVMValue, value: &LLVMValue, index: usize) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = insertvalue {} {array}, {} {value}, {index}", array.val_type(), value.val_type()))
+	}
+
+	// Write the message for an index that is out of bounds to stderr and abort. Output printed so far is flushed first,
+	// as abort doesn't. abort is cold, so the block this ends is too. Uses two unnamed registers.
+	pub fn write_bounds_check_failure(&mut self, message: &LLVMValue, index: &LLVMValue) -> Result<()> {
+		self.writeln("\tcall i32 @fflush(i8* null)")?;
+		self.writeln(&format!("\tcall i32(i32, i8*, ...) @dprintf(i32 2, i8* {message}, {} {index})", index.val_type()))?;
+		self.writeln("\tcall void @abort()")?;
+		self.writeln("\tunreachable")
+	}
+
+	// Call printf with a format string and arguments that are already widened
+	pub fn write_printf(&mut self, fstring: &LLVMValue, args: &[LLVMValue]) -> Result<()> {
+		let args: String = args.iter().map(|arg| format!(", {} {arg}", arg.val_type())).collect();
+		self.writeln(&format!("\tcall i32(i8*, ...) @printf(i8* {fstring}{args})"))
+	}
+
+	pub fn write_select(&mut self, reg: u32, condition: &LLVMValue, t_val: &LLVMValue, f_val: &LLVMValue) -> Result<()> {
+		self.writeln(&format!("\t%{reg} = select i1 {condition}, {} {t_val}, {} {f_val}", t_val.val_type(), f_val.val_type()))
+	}
+
+	// Define the named type of a struct, e.g. %struct.Point = type { i64, i64 }
+	pub fn write_struct_type(&mut self, layout: &StructLayout) -> Result<()> {
+		let fields: Vec<String> = layout.fields().iter().map(|(_, format)| format.format_type()).collect();
+		self.writeln(&format!("{} = type {{ {} }}", layout.format().format_type(), fields.join(", ")))
+	}
+
+	// Write the global holding a string literal, with its NUL terminator
+	pub fn write_string_constant(&mut self, index: usize, bytes: &[u8]) -> Result<()> {
+		let escaped: String = bytes.iter().map(|&byte| match byte {
+			b' '..=b'~' if byte != b'"' && byte != b'\\' => (byte as char).to_string(),
+			_ => format!("\\{byte:02X}"),
+		}).collect();
+		self.writeln(&format!("@str.{index} = private unnamed_addr constant [{} x i8] c\"{escaped}\\00\", align 1", bytes.len() + 1))
 	}
 
 	pub fn write(&mut self, msg: &str) -> Result<()> {
-		self.target.write(msg.as_bytes())
-			.map(|_| Ok(()))
-			.map_err(|cause| Error::FileWriteError { cause })?
+		self.target.write_all(msg.as_bytes())
+			.map_err(|cause| Error::FileWriteError { cause })
 	}
 
 	pub fn writeln(&mut self, msg: &str) -> Result<()> {
-		self.target.write((msg.to_owned() + "\n").as_bytes())
-			.map(|_| Ok(()))
-			.map_err(|cause| Error::FileWriteError { cause })?
+		self.write(msg)?;
+		self.write("\n")
 	}
 
+}
+
+// Float arithmetic instructions are the integer ones prefixed with f
+fn float_prefix(value: &LLVMValue) -> &'static str {
+	if value.format().is_float() { "f" } else { "" }
+}
+
+// nsw is only given to signed arithmetic, as unsigned values are expected to wrap
+fn no_signed_wrap(value: &LLVMValue) -> &'static str {
+	if value.format().is_signed() { " nsw" } else { "" }
 }
\ No newline at end of file
@@ -0,0 +1,712 @@
+pub mod value;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::error::*;
+use crate::generating::TYPE_FORMATS;
+use crate::generating::folding::{fold_constant, is_constant};
+use crate::generating::llvm::{EnumLayout, RegisterFormat};
+use crate::parsing::ast::{ASTNode, Type};
+use crate::scanning::token::*;
+use value::Value;
+
+// Deepest the interpreted program may recurse before it is stopped, and a stack size that leaves room for it
+pub const MAX_CALL_DEPTH: usize = 4096;
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// String literals live at addresses from here on, apart from the locals, so they outlive the frame that used them
+const STRING_ADDRESS: usize = 1 << 40;
+
+// How a statement finished; a return unwinds every block up to the function it is in
+enum Flow {
+	Normal,
+	Return(Value),
+}
+
+// A local's storage; the format is kept so references to it know what they point at
+#[derive(Debug)]
+struct Slot {
+	format: RegisterFormat,
+	value: Value,
+}
+
+// Runs a program by walking its AST. Programs are expected to have been checked by the generator first,
+// so only errors that can't be found before running are reported with any care.
+#[derive(Debug)]
+pub struct Interpreter<'a, W: Write> {
+	out: W,
+	functions: HashMap<&'a str, &'a ASTNode>,
+	// Names and formats of the fields of each struct, in the order they are laid out in
+	structs: HashMap<&'a str, Vec<(&'a str, RegisterFormat)>>,
+	enums: HashMap<&'a str, EnumLayout>,
+	memory: Vec<Slot>,
+	frames: Vec<HashMap<&'a str, usize>>,
+	// Format each function being run returns, which its return statements are folded as
+	return_formats: Vec<RegisterFormat>,
+	strings: Vec<Slot>,
+	string_addresses: HashMap<&'a [u8], usize>,
+}
+
+impl<'a, W: Write> Interpreter<'a, W> {
+	// Printed values are written to out
+	pub fn new(out: W) -> Self {
+		Self {
+			out,
+			functions: HashMap::new(),
+			structs: HashMap::new(),
+			enums: HashMap::new(),
+			memory: Vec::new(),
+			frames: Vec::new(),
+			return_formats: Vec::new(),
+			strings: Vec::new(),
+			string_addresses: HashMap::new(),
+		}
+	}
+
+	// Run the program's main function and return what it returned
+	pub fn run(&mut self, program: &'a [ASTNode]) -> Result<Value> {
+		for node in program {
+			match node {
+				ASTNode::FunctionDefinition { name, .. } => { self.functions.insert(name, node); },
+				ASTNode::StructDefinition { name, .. } => { self.structs.insert(name, Vec::new()); },
+				ASTNode::EnumDefinition { name, variants, span } => {
+					let mut layout = EnumLayout::new(name, span);
+					for variant in variants {
+						layout.push_variant(&variant.name, variant.discriminant).map_err(|error| error.at(&variant.span))?;
+					}
+					self.enums.insert(name, layout);
+				},
+				_ => {},
+			}
+		}
+
+		// Fields can be of structs declared after them, so they are only looked at once every struct is known
+		for node in program {
+			if let ASTNode::StructDefinition { name, fields, .. } = node {
+				let fields = fields.iter()
+					.map(|field| Ok((field.name.as_str(), self.format_from_type(&field.field_type).map_err(|error| error.at(&field.span))?)))
+					.collect::<Result<Vec<_>>>()?;
+				self.structs.insert(name, fields);
+			}
+		}
+
+		let result = self.call("main", Vec::new());
+		self.out.flush().map_err(|cause| Error::FileWriteError { cause })?;
+
+		result
+	}
+
+	// Call a function with already evaluated arguments
+	pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value> {
+		let Some(ASTNode::FunctionDefinition { name, parameters, body_block, return_type, signature_span, .. }) = self.functions.get(name).copied() else {
+			return Err(Error::SymbolUndefined { name: name.to_owned() });
+		};
+		if self.frames.len() >= MAX_CALL_DEPTH {
+			return Err(Error::StackOverflow { limit: MAX_CALL_DEPTH });
+		}
+
+		// Parameters are copied into locals, like the generator does
+		let memory_start = self.memory.len();
+		self.frames.push(HashMap::new());
+		for (param, arg) in parameters.iter().zip(args) {
+			let format = self.format_from_type(&param.param_type).map_err(|error| error.at(&param.span))?;
+			self.declare(&param.name, format, arg)?;
+		}
+
+		let return_fmt = self.format_from_type(return_type)?;
+		self.return_formats.push(return_fmt.clone());
+		let flow = self.exec_block(body_block);
+
+		self.return_formats.pop();
+		self.frames.pop();
+		self.memory.truncate(memory_start);
+
+		match flow? {
+			Flow::Return(value) => Ok(value.with_format(&return_fmt)),
+			Flow::Normal if matches!(return_type, Type::Void) => Ok(Value::Void),
+			Flow::Normal => Err(Error::MissingReturn { name: name.to_owned() }.at(signature_span)),
+		}
+	}
+
+	// Run each statement of a block until one returns
+	fn exec_block(&mut self, block: &'a [ASTNode]) -> Result<Flow> {
+		for statement in block {
+			if let Flow::Return(value) = self.exec(statement)? {
+				return Ok(Flow::Return(value));
+			}
+		}
+
+		Ok(Flow::Normal)
+	}
+
+	// Run a statement; errors are reported at the innermost node they occurred in
+	fn exec(&mut self, statement: &'a ASTNode) -> Result<Flow> {
+		self.exec_statement(statement).map_err(|error| error.at(statement.span()))
+	}
+
+	fn exec_statement(&mut self, statement: &'a ASTNode) -> Result<Flow> {
+		match statement {
+			ASTNode::Let { name, val_type, value, .. } => self.exec_let(name, val_type, value),
+			ASTNode::If { expr, block, else_block, .. } => {
+				if self.eval(expr)?.as_boolean()? {
+					self.exec_block(block)
+				} else if let Some(else_block) = else_block {
+					self.exec_block(else_block)
+				} else {
+					Ok(Flow::Normal)
+				}
+			},
+			ASTNode::While { expr, block, .. } => {
+				while self.eval(expr)?.as_boolean()? {
+					if let Flow::Return(value) = self.exec_block(block)? {
+						return Ok(Flow::Return(value));
+					}
+				}
+
+				Ok(Flow::Normal)
+			},
+			ASTNode::Return { return_val, .. } => {
+				let return_fmt = self.return_formats.last().expect("returns are only run inside a function").clone();
+				let value = match return_val {
+					Some(return_val) => self.eval_expecting(return_val, &return_fmt)?,
+					None => Value::Void,
+				};
+
+				Ok(Flow::Return(value))
+			},
+			ASTNode::Print { expr, .. } => {
+				let value = self.eval(expr)?;
+				// Chars and strings are written as raw bytes, which aren't always valid UTF-8
+				let bytes = match value {
+					Value::Char(x) => vec![x],
+					Value::Pointer { address, pointee: RegisterFormat::Char } => self.read_string(address)?,
+					_ => value.to_string().into_bytes(),
+				};
+				self.out.write_all(&bytes)
+					.and_then(|_| self.out.write_all(b"\n"))
+					.map_err(|cause| Error::FileWriteError { cause })?;
+
+				Ok(Flow::Normal)
+			},
+			ASTNode::FormattedPrint { pieces, args, .. } => {
+				let mut bytes = pieces[0].clone();
+				for (arg, piece) in args.iter().zip(&pieces[1..]) {
+					let value = self.eval(arg)?;
+					bytes.extend(self.format_argument(value)?);
+					bytes.extend(piece);
+				}
+				self.out.write_all(&bytes).map_err(|cause| Error::FileWriteError { cause })?;
+
+				Ok(Flow::Normal)
+			},
+			ASTNode::FunctionDefinition { .. } | ASTNode::StructDefinition { .. } | ASTNode::EnumDefinition { .. } => Err(Error::StatementExpected),
+			_ => self.eval(statement).map(|_| Flow::Normal),
+		}
+	}
+
+	fn exec_let(&mut self, name: &'a str, val_type: &Option<Type>, value: &'a Option<Box<ASTNode>>) -> Result<Flow> {
+		let declared_fmt = val_type.as_ref().map(|val_type| self.format_from_type(val_type)).transpose()?;
+		let value = match (value, &declared_fmt) {
+			(Some(value), Some(format)) => Some(self.eval_expecting(value, format)?),
+			(Some(value), None) => Some(self.eval(value)?),
+			(None, _) => None,
+		};
+
+		// A let in a loop body runs again on each iteration, but still names the same local
+		if let Some(&address) = self.frame().get(name) {
+			if let Some(value) = value {
+				self.write(address, value)?;
+			}
+
+			return Ok(Flow::Normal);
+		}
+
+		let format = match (declared_fmt, &value) {
+			(Some(format), _) => format,
+			(None, Some(value)) => value.format(),
+			(None, None) => RegisterFormat::INT,
+		};
+		let value = value.unwrap_or_else(|| Value::zero(&format));
+		self.declare(name, format, value)?;
+
+		Ok(Flow::Normal)
+	}
+
+	// Evaluate an expression to a value; errors are reported at the innermost node they occurred in
+	fn eval(&mut self, expr: &'a ASTNode) -> Result<Value> {
+		self.eval_expression(expr).map_err(|error| error.at(expr.span()))
+	}
+
+	fn eval_expression(&mut self, expr: &'a ASTNode) -> Result<Value> {
+		// Number literals are folded too, so they aren't matched below
+		if let Some(constant) = fold_constant(expr, None)? {
+			return Ok(Value::from_constant(constant));
+		}
+
+		match expr {
+			ASTNode::Literal { value: Literal::Char(x), .. } => Ok(Value::Char(*x)),
+			ASTNode::Literal { value: Literal::String(bytes), .. } => Ok(Value::Pointer { address: self.intern_string(bytes), pointee: RegisterFormat::Char }),
+			ASTNode::Literal { value: Literal::Boolean(x), .. } => Ok(Value::Boolean(*x)),
+			ASTNode::Literal { .. } | ASTNode::Dereference { .. } | ASTNode::Index { .. } | ASTNode::FieldAccess { .. } => {
+				let address = self.eval_place(expr)?;
+				self.read(address)
+			},
+			ASTNode::ArrayLiteral { elements, .. } => {
+				let elements = elements.iter().map(|element| self.eval(element)).collect::<Result<Vec<Value>>>()?;
+				Ok(Value::array(elements))
+			},
+			ASTNode::ArrayRepeat { value, count, .. } => Ok(Value::array(vec![self.eval(value)?; *count])),
+			ASTNode::Path { type_name, member, .. } => {
+				let layout = self.enums.get(type_name.as_str()).ok_or_else(|| Error::TypeUnknown { received: Type::Named { type_name: type_name.to_owned() } })?;
+				Ok(Value::Integer(layout.variant(member)?, layout.format()))
+			},
+			ASTNode::StructLiteral { name, fields, .. } => {
+				// Evaluated in the order they are given, then put in the order they are declared in
+				let layout = self.structs.get(name.as_str()).ok_or_else(|| Error::TypeUnknown { received: Type::Named { type_name: name.to_owned() } })?.clone();
+				let mut values = HashMap::new();
+				for field in fields {
+					let value = match layout.iter().find(|(name, _)| *name == field.name) {
+						Some((_, format)) => self.eval_expecting(&field.value, format)?,
+						None => self.eval(&field.value)?,
+					};
+					values.insert(field.name.as_str(), value);
+				}
+				let fields = layout.iter()
+					.map(|(field, _)| values.remove(field).ok_or_else(|| Error::MissingFields { name: name.to_owned(), fields: vec![field.to_string()] }))
+					.collect::<Result<Vec<Value>>>()?;
+
+				Ok(Value::Struct(fields, RegisterFormat::Struct { name: name.to_owned() }))
+			},
+			ASTNode::Reference { child, .. } => {
+				let address = self.eval_place(child)?;
+				Ok(Value::Pointer { address, pointee: self.slot(address)?.format.clone() })
+			},
+			ASTNode::Unary { token, child, .. } if token.is_increment() => self.eval_increment(token, child, false),
+			ASTNode::Postfix { token, child, .. } => self.eval_increment(token, child, true),
+			ASTNode::Cast { child, target_type, .. } => {
+				let format = self.format_from_type(target_type)?;
+				self.eval(child)?.cast(&format)
+			},
+			ASTNode::Unary { token, child, .. } => {
+				match (token, self.eval(child)?) {
+					(Token::Minus, Value::Integer(x, format)) => Ok(Value::integer(x.wrapping_neg(), &format)),
+					(Token::Minus, Value::Float(x, format)) => Ok(Value::float(-x, &format)),
+					(Token::Exclamation, Value::Boolean(x)) => Ok(Value::Boolean(!x)),
+					(Token::Tilde, Value::Integer(x, format)) => Ok(Value::integer(!x, &format)),
+					(_, value) => Err(Error::InvalidUnaryOperand { operator: token.clone(), received: value.format() }),
+				}
+			},
+			ASTNode::Binary { token: Token::Equals, left, right, .. } => {
+				let address = self.eval_place(left)?;
+				let format = self.slot(address)?.format.clone();
+				let value = self.eval_expecting(right, &format)?;
+
+				self.write(address, value)
+			},
+			ASTNode::Binary { token, left, right, .. } if token.compound_operator().is_some() => {
+				let address = self.eval_place(left)?;
+				let format = self.slot(address)?.format.clone();
+				let right = self.eval_expecting(right, &format)?;
+				let operator = token.compound_operator().expect("checked by the guard");
+				let value = self.eval_binary(&operator, self.slot(address)?.value.clone(), right)?;
+				self.slot_mut(address)?.value = value.clone();
+
+				Ok(value)
+			},
+			ASTNode::Binary { token, left, right, .. } if token.is_logical() => {
+				// The right operand is only evaluated if the left one doesn't decide the result
+				let left = self.eval(left)?.as_boolean()?;
+				match token {
+					Token::Ampersand2 if !left => Ok(Value::Boolean(false)),
+					Token::Pipe2 if left => Ok(Value::Boolean(true)),
+					_ => Ok(Value::Boolean(self.eval(right)?.as_boolean()?)),
+				}
+			},
+			ASTNode::Binary { token, left, right, .. } => {
+				let (left, right) = self.eval_operands(left, right)?;
+
+				self.eval_binary(token, left, right)
+			},
+			ASTNode::FunctionCall { name, args, .. } => {
+				// Arguments made only of literals are folded as the format of their parameter, like in the generator
+				let params = match self.functions.get(name.as_str()) {
+					Some(ASTNode::FunctionDefinition { parameters, .. }) => parameters.iter().map(|param| self.format_from_type(&param.param_type)).collect::<Result<Vec<_>>>()?,
+					_ => Vec::new(),
+				};
+				let mut arg_vals = Vec::new();
+				for (i, arg) in args.iter().enumerate() {
+					arg_vals.push(match params.get(i) {
+						Some(format) => self.eval_expecting(arg, format)?,
+						None => self.eval(arg)?,
+					});
+				}
+
+				self.call(name, arg_vals)
+			},
+			_ => Err(Error::ExpressionExpected),
+		}
+	}
+
+	// Evaluate an expression whose value is used as format; if it is made only of literals it is folded as that format, like in the generator
+	fn eval_expecting(&mut self, expr: &'a ASTNode, format: &RegisterFormat) -> Result<Value> {
+		// The elements of an array literal are used as the element format
+		match (expr, format) {
+			(ASTNode::ArrayLiteral { elements, .. }, RegisterFormat::Array { element, .. }) => {
+				let elements = elements.iter().map(|value| self.eval_expecting(value, element)).collect::<Result<Vec<Value>>>()?;
+				Ok(Value::array(elements))
+			},
+			(ASTNode::ArrayRepeat { value, count, .. }, RegisterFormat::Array { element, .. }) => Ok(Value::array(vec![self.eval_expecting(value, element)?; *count])),
+			_ => match fold_constant(expr, Some(format))? {
+				Some(constant) => Ok(Value::from_constant(constant)),
+				None => self.eval(expr),
+			},
+		}
+	}
+
+	// Evaluate the operands of a binary operator. One made only of literals is folded as the format of the other,
+	// which is evaluated first if it is the right one; the literals have no side effects, so the order doesn't matter.
+	fn eval_operands(&mut self, left: &'a ASTNode, right: &'a ASTNode) -> Result<(Value, Value)> {
+		if is_constant(left) && !is_constant(right) {
+			let right = self.eval(right)?;
+			Ok((self.eval_expecting(left, &right.format())?, right))
+		} else {
+			let left = self.eval(left)?;
+			let right = self.eval_expecting(right, &left.format())?;
+			Ok((left, right))
+		}
+	}
+
+	// Apply a binary operator. Arithmetic wraps on overflow, which the generated code only does for unsigned values;
+	// signed overflow is undefined there (nsw), so programs relying on it can behave differently when compiled
+	fn eval_binary(&mut self, token: &Token, left: Value, right: Value) -> Result<Value> {
+		if let (Value::Boolean(left), Value::Boolean(right)) = (&left, &right) {
+			return match token {
+				Token::Equals2 => Ok(Value::Boolean(left == right)),
+				Token::ExclamationEqual => Ok(Value::Boolean(left != right)),
+				_ => Err(Error::InvalidComparisonOperands { left: RegisterFormat::Boolean, right: RegisterFormat::Boolean }),
+			};
+		}
+
+		// Chars can only be compared, which they are as u8
+		if let (Value::Char(left), Value::Char(right)) = (&left, &right) {
+			if !token.is_comparison() {
+				return Err(Error::InvalidArithmeticOperand { received: RegisterFormat::Char });
+			}
+			let format = RegisterFormat::Char.as_integer();
+			return self.eval_binary(token, Value::integer(*left as i64, &format), Value::integer(*right as i64, &format));
+		}
+
+		// A literal operand takes on the format of the other operand, like in the generator
+		let format = match (left.format(), right.format()) {
+			(RegisterFormat::INT | RegisterFormat::F64, format) | (format, _) => format,
+		};
+		if let (Value::Float(left, _), Value::Float(right, _)) = (&left, &right) {
+			return Self::eval_float_binary(token, *left, *right, &format);
+		}
+
+		let signed = format.is_signed();
+		let left = left.as_integer()?;
+		let right = right.as_integer()?;
+
+		// Unsigned values are zero extended, so they compare and divide correctly as u64
+		let (unsigned_left, unsigned_right) = (left as u64, right as u64);
+		let value = match token {
+			Token::Plus => left.wrapping_add(right),
+			Token::Minus => left.wrapping_sub(right),
+			Token::Asterisk => left.wrapping_mul(right),
+			Token::Slash | Token::Percent if right == 0 => return Err(Error::DivisionByZero),
+			Token::Slash if signed => left.wrapping_div(right),
+			Token::Slash => (unsigned_left / unsigned_right) as i64,
+			Token::Percent if signed => left.wrapping_rem(right),
+			Token::Percent => (unsigned_left % unsigned_right) as i64,
+			Token::Ampersand => left & right,
+			Token::Pipe => left | right,
+			Token::Caret => left ^ right,
+			// Shifting by the width or more is undefined in the generated code; here only the low bits of the amount count
+			Token::LessThan2 => left.wrapping_shl(right as u32),
+			Token::GreaterThan2 if signed => left.wrapping_shr(right as u32),
+			Token::GreaterThan2 => unsigned_left.wrapping_shr(right as u32) as i64,
+			Token::Equals2 => return Ok(Value::Boolean(left == right)),
+			Token::ExclamationEqual => return Ok(Value::Boolean(left != right)),
+			Token::LessThan if signed => return Ok(Value::Boolean(left < right)),
+			Token::LessThanEqual if signed => return Ok(Value::Boolean(left <= right)),
+			Token::GreaterThan if signed => return Ok(Value::Boolean(left > right)),
+			Token::GreaterThanEqual if signed => return Ok(Value::Boolean(left >= right)),
+			Token::LessThan => return Ok(Value::Boolean(unsigned_left < unsigned_right)),
+			Token::LessThanEqual => return Ok(Value::Boolean(unsigned_left <= unsigned_right)),
+			Token::GreaterThan => return Ok(Value::Boolean(unsigned_left > unsigned_right)),
+			Token::GreaterThanEqual => return Ok(Value::Boolean(unsigned_left >= unsigned_right)),
+			_ => return Err(Error::BinaryOperatorExpected { received: token.clone() }),
+		};
+
+		Ok(Value::integer(value, &format))
+	}
+
+	// Apply a binary operator to floats; division by zero gives an infinity or NaN like in the generated code
+	fn eval_float_binary(token: &Token, left: f64, right: f64, format: &RegisterFormat) -> Result<Value> {
+		let value = match token {
+			Token::Plus => left + right,
+			Token::Minus => left - right,
+			Token::Asterisk => left * right,
+			Token::Slash => left / right,
+			Token::Equals2 => return Ok(Value::Boolean(left == right)),
+			Token::ExclamationEqual => return Ok(Value::Boolean(left != right)),
+			Token::LessThan => return Ok(Value::Boolean(left < right)),
+			Token::LessThanEqual => return Ok(Value::Boolean(left <= right)),
+			Token::GreaterThan => return Ok(Value::Boolean(left > right)),
+			Token::GreaterThanEqual => return Ok(Value::Boolean(left >= right)),
+			_ => return Err(Error::InvalidArithmeticOperand { received: format.clone() }),
+		};
+
+		Ok(Value::float(value, format))
+	}
+
+	// Apply ++ or -- to an lvalue, giving the value it had before if postfix and the one it has after otherwise
+	fn eval_increment(&mut self, token: &Token, child: &'a ASTNode, postfix: bool) -> Result<Value> {
+		let address = self.eval_place(child)?;
+		let before = self.slot(address)?.value.clone();
+		let Value::Integer(x, format) = &before else {
+			return Err(Error::InvalidUnaryOperand { operator: token.clone(), received: before.format() });
+		};
+		let after = match token {
+			Token::Plus2 => Value::integer(x.wrapping_add(1), format),
+			_ => Value::integer(x.wrapping_sub(1), format),
+		};
+		self.slot_mut(address)?.value = after.clone();
+
+		Ok(if postfix { before } else { after })
+	}
+
+	// Evaluate an lvalue to the address it names
+	fn eval_place(&mut self, expr: &'a ASTNode) -> Result<usize> {
+		match expr {
+			ASTNode::Literal { value: Literal::Identifier(Identifier::Symbol(name)), .. } => {
+				self.frame().get(name.as_str()).copied().ok_or_else(|| Error::SymbolUndefined { name: name.to_owned() })
+			},
+			ASTNode::Dereference { child, .. } => {
+				let address = self.eval(child)?.as_address()?;
+				self.slot(address)?;
+
+				Ok(address)
+			},
+			ASTNode::Index { child, index, .. } => {
+				let address = self.eval_stored(child)?;
+				let RegisterFormat::Array { element, len } = self.slot(address)?.format.clone() else {
+					return Err(Error::NotIndexable { received: self.slot(address)?.format.clone() });
+				};
+
+				// Always checked, as if the generated code had bounds checks, and reported at the index like there
+				let value = self.eval(index)?.as_integer()?;
+				if value < 0 || value as usize >= len {
+					return Err(Error::IndexOutOfBounds { len, index: value.into() }.at(index.span()));
+				}
+
+				Ok(address + 1 + value as usize * self.size_of(&element))
+			},
+			ASTNode::FieldAccess { child, field, .. } => {
+				let mut address = self.eval_stored(child)?;
+				// A pointer to a struct is dereferenced first, so p.x is (*p).x
+				if let RegisterFormat::Pointer { pointee } = &self.slot(address)?.format {
+					if let RegisterFormat::Struct { .. } = **pointee {
+						address = self.read(address)?.as_address()?;
+					}
+				}
+				let RegisterFormat::Struct { name } = self.slot(address)?.format.clone() else {
+					return Err(Error::NotAStruct { received: self.slot(address)?.format.clone() });
+				};
+
+				let mut offset = 1;
+				for (field_name, format) in &self.structs[name.as_str()] {
+					if field_name == field {
+						return Ok(address + offset);
+					}
+					offset += self.size_of(format);
+				}
+
+				Err(Error::UnknownField { name, field: field.to_owned() })
+			},
+			_ => Err(Error::ExpectedLValue),
+		}
+	}
+
+	// Address of the value of an expression. A value that isn't stored anywhere, e.g. one returned by a call,
+	// is stored in a temporary first.
+	fn eval_stored(&mut self, expr: &'a ASTNode) -> Result<usize> {
+		match expr {
+			ASTNode::Literal { .. } | ASTNode::Dereference { .. } | ASTNode::Index { .. } | ASTNode::FieldAccess { .. } => self.eval_place(expr),
+			_ => {
+				let value = self.eval(expr)?;
+				let address = self.allocate(&value.format());
+				self.write(address, value)?;
+				Ok(address)
+			},
+		}
+	}
+
+	// Storage at address. Pointers into frames that have returned are caught as long as the memory wasn't reused.
+	fn slot(&self, address: usize) -> Result<&Slot> {
+		match address.checked_sub(STRING_ADDRESS) {
+			Some(offset) => self.strings.get(offset),
+			None => self.memory.get(address),
+		}.ok_or(Error::InvalidPointer)
+	}
+
+	// Storage at address that is about to be written; string literals are constants in the generated code
+	fn slot_mut(&mut self, address: usize) -> Result<&mut Slot> {
+		if address >= STRING_ADDRESS {
+			return Err(Error::ReadOnlyWrite);
+		}
+
+		self.memory.get_mut(address).ok_or(Error::InvalidPointer)
+	}
+
+	// Address of the first byte of a string literal, which equal literals share
+	fn intern_string(&mut self, bytes: &'a [u8]) -> usize {
+		if let Some(&address) = self.string_addresses.get(bytes) {
+			return address;
+		}
+
+		let address = STRING_ADDRESS + self.strings.len();
+		for &byte in bytes.iter().chain(&[0]) {
+			self.strings.push(Slot { format: RegisterFormat::Char, value: Value::Char(byte) });
+		}
+		self.string_addresses.insert(bytes, address);
+
+		address
+	}
+
+	// Text a {} placeholder is replaced with, as the printf conversion the generator picks for the value prints it
+	fn format_argument(&self, value: Value) -> Result<Vec<u8>> {
+		match value {
+			Value::Boolean(x) => Ok(x.to_string().into_bytes()),
+			Value::Char(x) => Ok(vec![x]),
+			Value::Pointer { address, pointee: RegisterFormat::Char } => self.read_string(address),
+			Value::Pointer { address, .. } => Ok(format!("{address:#x}").into_bytes()),
+			Value::Null => Ok(b"(nil)".to_vec()),
+			Value::Void => Err(Error::ExpressionExpected),
+			_ => Ok(value.to_string().into_bytes()),
+		}
+	}
+
+	// Bytes from address up to the NUL that ends them, like printf's %s reads them
+	fn read_string(&self, mut address: usize) -> Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		loop {
+			match self.slot(address)?.value {
+				Value::Char(0) => return Ok(bytes),
+				Value::Char(byte) => bytes.push(byte),
+				_ => return Err(Error::InvalidPointer),
+			}
+			address += 1;
+		}
+	}
+
+	// Give a new local of the current frame its storage
+	fn declare(&mut self, name: &'a str, format: RegisterFormat, value: Value) -> Result<()> {
+		let address = self.allocate(&format);
+		self.write(address, value)?;
+		self.frame().insert(name, address);
+
+		Ok(())
+	}
+
+	// Storage for a value of the format, which is zero until written. An array or struct takes a slot holding its format
+	// followed by the slots of its elements or fields, so they have addresses of their own.
+	fn allocate(&mut self, format: &RegisterFormat) -> usize {
+		let address = self.memory.len();
+		match format {
+			RegisterFormat::Array { element, len } => {
+				self.memory.push(Slot { format: format.clone(), value: Value::Void });
+				for _ in 0..*len {
+					self.allocate(element);
+				}
+			},
+			RegisterFormat::Struct { name } => {
+				self.memory.push(Slot { format: format.clone(), value: Value::Void });
+				for format in self.field_formats(name) {
+					self.allocate(&format);
+				}
+			},
+			_ => self.memory.push(Slot { format: format.clone(), value: Value::zero(format) }),
+		}
+
+		address
+	}
+
+	// Formats of the fields of the named struct, in the order they are laid out in
+	fn field_formats(&self, name: &str) -> Vec<RegisterFormat> {
+		self.structs.get(name).map_or_else(Vec::new, |fields| fields.iter().map(|(_, format)| format.clone()).collect())
+	}
+
+	// Number of slots a value of the format takes
+	fn size_of(&self, format: &RegisterFormat) -> usize {
+		match format {
+			RegisterFormat::Array { element, len } => 1 + len * self.size_of(element),
+			RegisterFormat::Struct { name } => 1 + self.field_formats(name).iter().map(|format| self.size_of(format)).sum::<usize>(),
+			_ => 1,
+		}
+	}
+
+	// Addresses of the elements or fields of the array or struct at address, with their formats
+	fn members(&self, address: usize, format: &RegisterFormat) -> Vec<(usize, RegisterFormat)> {
+		let formats = match format {
+			RegisterFormat::Array { element, len } => vec![(**element).clone(); *len],
+			RegisterFormat::Struct { name } => self.field_formats(name),
+			_ => Vec::new(),
+		};
+
+		let mut offset = address + 1;
+		formats.into_iter().map(|format| {
+			let member = (offset, format);
+			offset += self.size_of(&member.1);
+			member
+		}).collect()
+	}
+
+	// Value stored at address
+	fn read(&self, address: usize) -> Result<Value> {
+		let slot = self.slot(address)?;
+		let values = || self.members(address, &slot.format).into_iter().map(|(member, _)| self.read(member)).collect::<Result<Vec<Value>>>();
+		match &slot.format {
+			RegisterFormat::Array { .. } => Ok(Value::Array(values()?, slot.format.clone())),
+			RegisterFormat::Struct { .. } => Ok(Value::Struct(values()?, slot.format.clone())),
+			_ => Ok(slot.value.clone()),
+		}
+	}
+
+	// Store value at address, as the format of the storage; gives back what was stored
+	fn write(&mut self, address: usize, value: Value) -> Result<Value> {
+		let slot = self.slot_mut(address)?;
+		let value = value.with_format(&slot.format);
+		let format = slot.format.clone();
+		let (Value::Array(values, _) | Value::Struct(values, _)) = &value else {
+			slot.value = value.clone();
+			return Ok(value);
+		};
+
+		for ((member, _), value) in self.members(address, &format).into_iter().zip(values) {
+			self.write(member, value.clone())?;
+		}
+
+		Ok(value)
+	}
+
+	fn frame(&mut self) -> &mut HashMap<&'a str, usize> {
+		self.frames.last_mut().expect("locals are only used inside a function")
+	}
+
+	fn format_from_type(&self, source: &Type) -> Result<RegisterFormat> {
+		match source {
+			Type::Named { type_name } if self.structs.contains_key(type_name.as_str()) => Ok(RegisterFormat::Struct { name: type_name.to_owned() }),
+			Type::Named { type_name } if self.enums.contains_key(type_name.as_str()) => Ok(RegisterFormat::Enum { name: type_name.to_owned() }),
+			Type::Named { type_name } => TYPE_FORMATS.iter()
+				.find(|type_fmt| type_name == type_fmt.0)
+				.map(|type_fmt| type_fmt.1.clone())
+				.ok_or_else(|| Error::TypeUnknown { received: source.to_owned() }),
+			Type::Pointer { pointee_type } => Ok(self.format_from_type(pointee_type)?.to_pointer()),
+			Type::Array { element_type, len } => Ok(RegisterFormat::Array { element: Box::new(self.format_from_type(element_type)?), len: *len }),
+			Type::Void => Ok(RegisterFormat::Void),
+		}
+	}
+}
\ No newline at end of file
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::generating::llvm::{Constant, RegisterFormat};
+
+// A value the interpreter computes with; pointers are addresses into its memory
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	Void,
+	Null,
+	Integer(i64, RegisterFormat),
+	Float(f64, RegisterFormat),
+	Char(u8),
+	Boolean(bool),
+	Pointer { address: usize, pointee: RegisterFormat },
+	Array(Vec<Value>, RegisterFormat),
+	// Fields in the order they are declared in
+	Struct(Vec<Value>, RegisterFormat),
+}
+
+impl Value {
+	// An integer of the given format, wrapped to its width. Values are kept sign extended if signed
+	// and zero extended otherwise, so they can be compared as i64 or u64.
+	pub fn integer(value: i64, format: &RegisterFormat) -> Value {
+		let value = match *format {
+			RegisterFormat::Integer { signed: true, width } => value << (64 - width) >> (64 - width),
+			RegisterFormat::Integer { signed: false, width } => ((value as u64) << (64 - width) >> (64 - width)) as i64,
+			_ => value,
+		};
+
+		Value::Integer(value, format.clone())
+	}
+
+	// Value of an expression the generator's folder folded. Integers above the range of int keep their bits,
+	// which are what the format they are used as gives them.
+	pub fn from_constant(constant: Constant) -> Value {
+		match constant {
+			Constant::Integer { value, format } => Value::integer(value as i64, &format),
+			Constant::Float { value, format } => Value::float(value, &format),
+			Constant::Boolean(x) => Value::Boolean(x),
+			constant => unreachable!("{constant:?} isn't folded"),
+		}
+	}
+
+	// A float of the given format; f32 values are rounded to single precision after each operation
+	pub fn float(value: f64, format: &RegisterFormat) -> Value {
+		match format {
+			RegisterFormat::Float { width: 32 } => Value::Float(value as f32 as f64, format.clone()),
+			_ => Value::Float(value, format.clone()),
+		}
+	}
+
+	// An array of elements, which take the format of the first one that isn't int or f64, like literals do in the generator
+	pub fn array(elements: Vec<Value>) -> Value {
+		let element = elements.iter()
+			.map(Value::format)
+			.find(|format| !matches!(*format, RegisterFormat::INT | RegisterFormat::F64))
+			.or_else(|| elements.first().map(Value::format))
+			.unwrap_or(RegisterFormat::Void);
+		let format = RegisterFormat::Array { element: Box::new(element), len: elements.len() };
+
+		Value::Array(elements, format.clone()).with_format(&format)
+	}
+
+	// Value of a local that was declared without one
+	pub fn zero(format: &RegisterFormat) -> Value {
+		match format {
+			RegisterFormat::Integer { .. } | RegisterFormat::Enum { .. } => Value::integer(0, format),
+			RegisterFormat::Float { .. } => Value::float(0.0, format),
+			RegisterFormat::Char => Value::Char(0),
+			RegisterFormat::Boolean => Value::Boolean(false),
+			RegisterFormat::Pointer { .. } => Value::Null,
+			RegisterFormat::Array { element, len } => Value::Array(vec![Value::zero(element); *len], format.clone()),
+			_ => Value::Void,
+		}
+	}
+
+	pub fn format(&self) -> RegisterFormat {
+		match self {
+			Value::Void => RegisterFormat::Void,
+			Value::Null => RegisterFormat::Null,
+			Value::Integer(_, format) | Value::Float(_, format) | Value::Array(_, format) | Value::Struct(_, format) => format.clone(),
+			Value::Char(_) => RegisterFormat::Char,
+			Value::Boolean(_) => RegisterFormat::Boolean,
+			Value::Pointer { pointee, .. } => pointee.to_pointer(),
+		}
+	}
+
+	pub fn as_integer(&self) -> Result<i64> {
+		match self {
+			Value::Integer(x, _) => Ok(*x),
+			_ => Err(Error::InvalidArithmeticOperand { received: self.format() }),
+		}
+	}
+
+	// The value as stored in a local of the given format; only literals differ from it, and only in format
+	pub fn with_format(self, format: &RegisterFormat) -> Value {
+		match (self, format) {
+			(Value::Integer(x, _), _) if format.is_integer() => Value::integer(x, format),
+			(Value::Float(x, _), _) if format.is_float() => Value::float(x, format),
+			(Value::Array(elements, _), RegisterFormat::Array { element, len }) if *len == elements.len() => {
+				Value::Array(elements.into_iter().map(|value| value.with_format(element)).collect(), format.clone())
+			},
+			(value, _) => value,
+		}
+	}
+
+	// Truth of a condition; pointers are coerced the same way the generator does it
+	pub fn as_boolean(&self) -> Result<bool> {
+		match self {
+			Value::Boolean(x) => Ok(*x),
+			Value::Pointer { .. } => Ok(true),
+			Value::Null => Ok(false),
+			_ => Err(Error::BadConversion { from: self.format(), to: RegisterFormat::Boolean }),
+		}
+	}
+
+	// Convert the value like an 'as' cast in the generated code does; chars convert like u8 and enums like int
+	pub fn cast(self, format: &RegisterFormat) -> Result<Value> {
+		match (self, format) {
+			(Value::Integer(x, RegisterFormat::Enum { .. }), _) => Value::integer(x, &RegisterFormat::INT).cast(format),
+			(value, RegisterFormat::Enum { .. }) => match value.cast(&RegisterFormat::INT)? {
+				Value::Integer(x, _) => Ok(Value::Integer(x, format.clone())),
+				value => Err(Error::BadConversion { from: value.format(), to: format.clone() }),
+			},
+			(Value::Char(x), RegisterFormat::Char) => Ok(Value::Char(x)),
+			(Value::Char(x), _) => Value::integer(x as i64, &RegisterFormat::Char.as_integer()).cast(format),
+			(value, RegisterFormat::Char) => match value.cast(&RegisterFormat::Char.as_integer())? {
+				Value::Integer(x, _) => Ok(Value::Char(x as u8)),
+				value => Err(Error::BadConversion { from: value.format(), to: format.clone() }),
+			},
+			(Value::Integer(x, _), RegisterFormat::Integer { .. }) => Ok(Value::integer(x, format)),
+			(Value::Float(x, _), RegisterFormat::Float { .. }) => Ok(Value::float(x, format)),
+			(Value::Integer(x, from), RegisterFormat::Float { .. }) if from.is_signed() => Ok(Value::float(x as f64, format)),
+			(Value::Integer(x, _), RegisterFormat::Float { .. }) => Ok(Value::float(x as u64 as f64, format)),
+			// Out of range floats are undefined in the generated code; here they saturate
+			(Value::Float(x, _), RegisterFormat::Integer { signed: true, .. }) => Ok(Value::integer(x as i64, format)),
+			(Value::Float(x, _), RegisterFormat::Integer { .. }) => Ok(Value::integer(x as u64 as i64, format)),
+			(Value::Float(x, _), RegisterFormat::Boolean) => Ok(Value::Boolean(x != 0.0)),
+			(Value::Boolean(x), RegisterFormat::Integer { .. }) => Ok(Value::integer(x as i64, format)),
+			(Value::Integer(x, _), RegisterFormat::Boolean) => Ok(Value::Boolean(x != 0)),
+			(value @ (Value::Boolean(_) | Value::Pointer { .. } | Value::Null), RegisterFormat::Boolean) => Ok(Value::Boolean(value.as_boolean()?)),
+			(Value::Pointer { address, .. }, RegisterFormat::Integer { .. }) => Ok(Value::integer(address as i64, format)),
+			(Value::Null, RegisterFormat::Integer { .. }) => Ok(Value::integer(0, format)),
+			(Value::Integer(0, _) | Value::Null, RegisterFormat::Pointer { .. }) => Ok(Value::Null),
+			(Value::Integer(x, _), RegisterFormat::Pointer { pointee }) => Ok(Value::Pointer { address: x as usize, pointee: (**pointee).clone() }),
+			(Value::Pointer { address, .. }, RegisterFormat::Pointer { pointee }) => Ok(Value::Pointer { address, pointee: (**pointee).clone() }),
+			(value, _) => Err(Error::BadConversion { from: value.format(), to: format.clone() }),
+		}
+	}
+
+	pub fn as_address(&self) -> Result<usize> {
+		match self {
+			Value::Pointer { address, .. } => Ok(*address),
+			Value::Null => Err(Error::InvalidPointer),
+			_ => Err(Error::InvalidDereference { received: self.format() }),
+		}
+	}
+}
+
+// Printed the way printf's %ld, %lu, %f and %c print the generated value
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Value::Void => write!(f, "void"),
+			Value::Null => write!(f, "0"),
+			Value::Integer(x, format) if format.is_integer() && !format.is_signed() => write!(f, "{}", *x as u64),
+			Value::Integer(x, _) => write!(f, "{x}"),
+			Value::Float(x, _) if x.is_nan() => write!(f, "{}nan", if x.is_sign_negative() { "-" } else { "" }),
+			Value::Float(x, _) => write!(f, "{x:.6}"),
+			Value::Char(x) => write!(f, "{}", *x as char),
+			Value::Boolean(x) => write!(f, "{}", *x as i64),
+			Value::Pointer { address, .. } => write!(f, "{address}"),
+			Value::Array(elements, _) => {
+				let elements: Vec<String> = elements.iter().map(Value::to_string).collect();
+				write!(f, "[{}]", elements.join(", "))
+			},
+			Value::Struct(fields, format) => {
+				let fields: Vec<String> = fields.iter().map(Value::to_string).collect();
+				write!(f, "{format} {{ {} }}", fields.join(", "))
+			},
+		}
+	}
+}
\ No newline at end of file
@@ -1,5 +1,74 @@
+// Errors carry the tokens and formats involved so they can be reported in detail
+#![allow(clippy::result_large_err)]
+
 pub mod cli;
 pub mod scanning;
 pub mod parsing;
 pub mod generating;
-pub mod error;
\ No newline at end of file
+pub mod interpreting;
+pub mod error;
+
+use error::{Error, Result};
+use generating::Generator;
+use generating::writer::Writer;
+use parsing::Parser;
+use scanning::Scanner;
+
+// Options for compiling source held in memory
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+	filename: String,
+	bounds_checks: bool,
+}
+
+impl CompileOptions {
+	// filename is the name spans are reported under and the module is given
+	pub fn new(filename: String) -> Self {
+		Self {
+			filename,
+			bounds_checks: false,
+		}
+	}
+
+	// Check array indexes at runtime, aborting if one is out of bounds
+	pub fn with_bounds_checks(mut self, bounds_checks: bool) -> Self {
+		self.bounds_checks = bounds_checks;
+		self
+	}
+
+	pub fn filename(&self) -> &str {
+		&self.filename
+	}
+
+	pub fn bounds_checks(&self) -> bool {
+		self.bounds_checks
+	}
+}
+
+impl Default for CompileOptions {
+	fn default() -> Self {
+		Self::new(String::from("<source>"))
+	}
+}
+
+// Compile source to LLVM without touching the filesystem.
+// Fails with CompileFailed holding every error found; warnings are not reported.
+pub fn compile_source(source: &str, options: &CompileOptions) -> Result<String> {
+	let mut parser = Parser::new(Scanner::from_str(options.filename().to_owned(), source))?;
+	let program = parser.parse_program();
+	if !parser.errors().is_empty() {
+		return Err(Error::CompileFailed { errors: parser.take_errors() });
+	}
+
+	let mut generator = Generator::new(Writer::in_memory(options.filename().to_owned()));
+	generator.set_bounds_checks(options.bounds_checks());
+	generator.generate(&program)?;
+	if !generator.errors().is_empty() {
+		return Err(Error::CompileFailed { errors: generator.take_errors() });
+	}
+
+	let output = generator.into_writer().into_inner()?;
+
+	// Only strings are ever written
+	Ok(String::from_utf8(output).expect("LLVM output is valid UTF-8"))
+}
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::scanning::span::Span;
 use crate::scanning::token::{Token, Literal};
 
 #[derive(Debug, Clone)]
@@ -8,6 +9,10 @@ pub enum Type {
 	Pointer {
 		pointee_type: Box<Type>,
 	},
+	Array {
+		element_type: Box<Type>,
+		len: usize,
+	},
 	Void
 }
 
@@ -16,6 +21,7 @@ impl std::fmt::Display for Type {
 		match self {
 			Type::Named { type_name } => write!(f, "{type_name}"),
 			Type::Pointer { pointee_type } => write!(f, "*{pointee_type}"),
+			Type::Array { element_type, len } => write!(f, "[{element_type}; {len}]"),
 			Type::Void => write!(f, "void"),
 		}
 	}
@@ -25,46 +31,185 @@ impl std::fmt::Display for Type {
 pub struct FunctionParameter {
 	pub name: String,
 	pub param_type: Type,
+	pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructField {
+	pub name: String,
+	pub field_type: Type,
+	pub span: Span,
+}
+
+// A variant of an enum; those without a discriminant are one more than the variant before them, or 0 if first
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+	pub name: String,
+	pub discriminant: Option<i128>,
+	pub span: Span,
+}
+
+// <name>: <value> in a struct literal
+#[derive(Debug, Clone)]
+pub struct FieldInitializer {
+	pub name: String,
+	pub value: ASTNode,
+	pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub enum ASTNode {
-	Literal(Literal),
-	Binary { token: Token, left: Box<ASTNode>, right: Box<ASTNode> },
+	Literal {
+		value: Literal,
+		span: Span,
+	},
+	Binary {
+		token: Token,
+		left: Box<ASTNode>,
+		right: Box<ASTNode>,
+		span: Span,
+	},
 	Print {
 		expr: Box<ASTNode>,
+		span: Span,
+	},
+	// Text of the format string between its placeholders, so there is one more piece than args
+	FormattedPrint {
+		pieces: Vec<Vec<u8>>,
+		args: Vec<ASTNode>,
+		span: Span,
 	},
 	Let { 
 		name: String,
+		name_span: Span,
 		val_type: Option<Type>,
+		type_span: Option<Span>,
 		value: Option<Box<ASTNode>>,
+		span: Span,
 	},
 	If {
 		expr: Box<ASTNode>,
 		block: Vec<ASTNode>,
 		else_block: Option<Vec<ASTNode>>,
+		span: Span,
 	},
 	While {
 		expr: Box<ASTNode>,
-		block: Vec<ASTNode>
+		block: Vec<ASTNode>,
+		span: Span,
 	},
 	FunctionDefinition {
 		name: String,
 		parameters: Vec<FunctionParameter>,
 		body_block: Vec<ASTNode>,
 		return_type: Type,
+		signature_span: Span,
+		span: Span,
+	},
+	StructDefinition {
+		name: String,
+		fields: Vec<StructField>,
+		span: Span,
+	},
+	EnumDefinition {
+		name: String,
+		variants: Vec<EnumVariant>,
+		span: Span,
 	},
 	FunctionCall {
 		name: String,
 		args: Vec<ASTNode>,
+		span: Span,
 	},
 	Return {
 		return_val: Option<Box<ASTNode>>,
+		span: Span,
+	},
+	Unary {
+		token: Token,
+		child: Box<ASTNode>,
+		span: Span,
+	},
+	Postfix {
+		token: Token,
+		child: Box<ASTNode>,
+		span: Span,
+	},
+	Cast {
+		child: Box<ASTNode>,
+		target_type: Type,
+		span: Span,
+	},
+	// [a, b, c]
+	ArrayLiteral {
+		elements: Vec<ASTNode>,
+		span: Span,
+	},
+	// [value; count]
+	ArrayRepeat {
+		value: Box<ASTNode>,
+		count: usize,
+		span: Span,
+	},
+	Index {
+		child: Box<ASTNode>,
+		index: Box<ASTNode>,
+		span: Span,
+	},
+	// Point { x: 1, y: 2 }
+	StructLiteral {
+		name: String,
+		fields: Vec<FieldInitializer>,
+		span: Span,
+	},
+	FieldAccess {
+		child: Box<ASTNode>,
+		field: String,
+		span: Span,
+	},
+	// Color::Red
+	Path {
+		type_name: String,
+		member: String,
+		span: Span,
 	},
 	Dereference {
 		child: Box<ASTNode>,
+		span: Span,
 	},
 	Reference {
 		child: Box<ASTNode>,
+		span: Span,
+	}
+}
+
+impl ASTNode {
+	// Location of the source text the node was parsed from
+	pub fn span(&self) -> &Span {
+		match self {
+			ASTNode::Literal { span, .. } => span,
+			ASTNode::Binary { span, .. } => span,
+			ASTNode::Print { span, .. } => span,
+			ASTNode::FormattedPrint { span, .. } => span,
+			ASTNode::Let { span, .. } => span,
+			ASTNode::If { span, .. } => span,
+			ASTNode::While { span, .. } => span,
+			ASTNode::FunctionDefinition { span, .. } => span,
+			ASTNode::StructDefinition { span, .. } => span,
+			ASTNode::EnumDefinition { span, .. } => span,
+			ASTNode::FunctionCall { span, .. } => span,
+			ASTNode::Return { span, .. } => span,
+			ASTNode::Unary { span, .. } => span,
+			ASTNode::Postfix { span, .. } => span,
+			ASTNode::Cast { span, .. } => span,
+			ASTNode::ArrayLiteral { span, .. } => span,
+			ASTNode::ArrayRepeat { span, .. } => span,
+			ASTNode::Index { span, .. } => span,
+			ASTNode::StructLiteral { span, .. } => span,
+			ASTNode::FieldAccess { span, .. } => span,
+			ASTNode::Path { span, .. } => span,
+			ASTNode::Dereference { span, .. } => span,
+			ASTNode::Reference { span, .. } => span,
+		}
 	}
 }
\ No newline at end of file
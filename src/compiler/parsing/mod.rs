@@ -1,5 +1,6 @@
 use crate::error::*;
 use crate::scanning::Scanner;
+use crate::scanning::span::Span;
 use crate::scanning::token::*;
 use ast::*;
 
@@ -8,28 +9,59 @@ pub mod ast;
 pub const OPERATOR_PRECEDENCE: &[(Token, u8)] = &[
 	(Token::Slash, 12),
 	(Token::Asterisk, 12),
+	(Token::Percent, 12),
 	(Token::Plus, 11),
 	(Token::Minus, 11),
+	(Token::LessThan2, 10),
+	(Token::GreaterThan2, 10),
 	(Token::GreaterThan, 9),
 	(Token::GreaterThanEqual, 9),
 	(Token::LessThan, 9),
 	(Token::LessThanEqual, 9),
 	(Token::Equals2, 8),
 	(Token::ExclamationEqual, 8),
+	// Binary '&'; as a prefix it is parsed in parse_terminal_node instead
+	(Token::Ampersand, 7),
+	(Token::Caret, 6),
+	(Token::Pipe, 5),
+	(Token::Ampersand2, 4),
+	(Token::Pipe2, 3),
 	(Token::Equals, 1),
+	(Token::PlusEqual, 1),
+	(Token::MinusEqual, 1),
+	(Token::AsteriskEqual, 1),
+	(Token::SlashEqual, 1),
+	(Token::PercentEqual, 1),
+	(Token::AmpersandEqual, 1),
+	(Token::PipeEqual, 1),
+	(Token::CaretEqual, 1),
+	(Token::LessThan2Equal, 1),
+	(Token::GreaterThan2Equal, 1),
 ];
 
 #[derive(Debug)]
 pub struct Parser {
 	scanner: Scanner,
 	current_token: Option<Token>,
+	current_span: Span,
+	previous_span: Span,
+	errors: Vec<Error>,
+	input_lost: bool,
+	// Off while parsing the condition of an if or while, where 'x {' starts the block rather than a struct literal
+	struct_literals: bool,
 }
 
 impl Parser {
 	pub fn new(scanner: Scanner) -> Result<Self> {
+		let current_span = scanner.current_span();
 		let mut parser = Self {
 			scanner,
 			current_token: None,
+			previous_span: current_span.clone(),
+			current_span,
+			errors: Vec::new(),
+			input_lost: false,
+			struct_literals: true,
 		};
 
 		parser.scan_next()?;
@@ -38,39 +70,148 @@ impl Parser {
 
 	// Scan next token into parser
 	pub fn scan_next(&mut self) -> Result<()> {
-		let token = self.scanner.scan()?;
+		let scanned = self.scanner.scan()?;
+
+		self.previous_span = self.current_span.clone();
+		match scanned {
+			Some((token, span)) => {
+				self.current_token = Some(token);
+				self.current_span = span;
+			},
+			None => { self.current_token = None; },
+		}
 
-		self.current_token = token;
 		Ok(())
 	}
 
+	// Span from the given start to the end of the last scanned token
+	pub fn span_from(&self, start: &Span) -> Span {
+		start.to(&self.previous_span)
+	}
+
+	pub fn current_span(&self) -> &Span {
+		&self.current_span
+	}
+
+	pub fn scanner(&self) -> &Scanner {
+		&self.scanner
+	}
+
+	// Syntax errors recovered from so far
+	pub fn errors(&self) -> &[Error] {
+		&self.errors
+	}
+
+	pub fn take_errors(&mut self) -> Vec<Error> {
+		std::mem::take(&mut self.errors)
+	}
+
+	// Record a syntax error. Once an error has cost the rest of the input, e.g. a comment that is never closed,
+	// any errors after it are only caused by the missing input and are dropped.
+	fn record_error(&mut self, error: Error) {
+		if self.input_lost {
+			return;
+		}
+
+		self.input_lost = matches!(error.root(), Error::FileReadError { .. } | Error::UnterminatedComment);
+		self.errors.push(error);
+	}
+
+	// Scan the next token while recovering; scan errors are recorded instead of returned.
+	// Returns false if scanning can't continue
+	fn skip_token(&mut self) -> bool {
+		match self.scan_next() {
+			Ok(()) => true,
+			Err(error) => {
+				let can_continue = !matches!(error.root(), Error::FileReadError { .. });
+				self.record_error(error);
+				if !can_continue {
+					self.current_token = Some(Token::EndOfFile);
+				}
+
+				can_continue
+			}
+		}
+	}
+
+	// Skip tokens until the start of the next statement: just past a ';' or a closed block, or before
+	// the '}' that closes the enclosing block. Stops before 'fn', 'struct', 'enum' or EOF as the block can't continue.
+	pub fn synchronize_statement(&mut self) {
+		let mut depth = 0;
+
+		loop {
+			match self.current_token {
+				Some(Token::EndOfFile) | Some(Token::Function) | Some(Token::Struct) | Some(Token::Enum) | None => return,
+				Some(Token::Semicolon) if depth == 0 => {
+					self.skip_token();
+					return;
+				},
+				Some(Token::LeftCurly) => depth += 1,
+				Some(Token::RightCurly) => {
+					if depth == 0 {
+						return;
+					}
+
+					depth -= 1;
+					if depth == 0 {
+						self.skip_token();
+						return;
+					}
+				},
+				_ => {},
+			}
+
+			if !self.skip_token() {
+				return;
+			}
+		}
+	}
+
+	// Skip tokens until the next global statement or EOF
+	pub fn synchronize_global(&mut self) {
+		while !matches!(self.current_token, Some(Token::EndOfFile) | Some(Token::Function) | Some(Token::Struct) | Some(Token::Enum) | None) {
+			if !self.skip_token() {
+				return;
+			}
+		}
+	}
+
+	// Parse every global statement in the file, recovering from syntax errors so all of them get reported
+	pub fn parse_program(&mut self) -> Vec<ASTNode> {
+		let mut program = Vec::new();
+
+		loop {
+			match self.parse_global_statement() {
+				Ok(Some(node)) => program.push(node),
+				Ok(None) => break,
+				Err(error) => {
+					self.record_error(error);
+					self.synchronize_global();
+				}
+			}
+		}
+
+		program
+	}
+
 	// Verify that token matches what is expected
 	pub fn match_token(&mut self, tokens: &[Token]) -> Result<Token> {
-		for (_, token) in tokens.iter().enumerate() {
-			let matched = match self.current_token.clone() {
-				Some(t) => *token == t,
-				None => false,
-			};
-
-			if matched {
-				return Ok(self.current_token.clone().unwrap());
+		for token in tokens.iter() {
+			if self.current_token.as_ref() == Some(token) {
+				return Ok(token.clone());
 			}
 		}
 
 		// Error handling
-		let t: Token;
-		if let Some(tok) = self.current_token.clone() { t = tok; } else {t = Token::None};
-		Err(Error::InvalidToken { expected: tokens.to_vec(), received: t })
+		let t = self.current_token.clone().unwrap_or(Token::None);
+		Err(Error::InvalidToken { expected: tokens.to_vec(), received: t }.at(&self.current_span))
 	}
 
 	// Verify that current token matches an identifier and return said identifier
 	pub fn match_identifier(&mut self) -> Result<Identifier> {
 		match self.current_token.clone() {
-			Some(t) => match t {
-				Token::Literal(Literal::Identifier(i)) => Ok(i),
-				_ => {Err(Error::IdentifierExpected { received: t })},
-			},
-			None => Err(Error::IdentifierExpected { received: Token::None })
+			Some(Token::Literal(Literal::Identifier(i))) => Ok(i),
+			t => Err(Error::IdentifierExpected { received: t.unwrap_or(Token::None) }.at(&self.current_span)),
 		}
 	}
 
@@ -78,18 +219,28 @@ impl Parser {
 	pub fn expect_identifier(&mut self, identifier: Identifier) -> Result<()> {
 		let matched_identifier = self.match_identifier()?;
 
-		if std::mem::discriminant(&matched_identifier) == std::mem::discriminant(&identifier) { Ok(()) } else { Err(Error::InvalidIdentifier { expected: [identifier].to_vec(), received: matched_identifier })}
+		if std::mem::discriminant(&matched_identifier) == std::mem::discriminant(&identifier) { Ok(()) } else { Err(Error::InvalidIdentifier { expected: [identifier].to_vec(), received: matched_identifier }.at(&self.current_span))}
 	}
 
 	// Parse type of current token(s)
 	pub fn parse_type(&mut self) -> Result<Type> {
-		let mut res: Type;
-		let type_id = self.match_identifier()?;
-		self.scan_next()?;
-		res = match type_id {
-			Identifier::Symbol(c) => Ok(Type::Named { type_name: c }),
-			_ => Err(Error::TypeExpected { received: type_id })
-		}?;
+		let mut res = if self.match_token(&[Token::LeftBracket]).is_ok() {
+			// Follows '[<type>; <len>]'
+			self.scan_next()?;
+			let element_type = Box::new(self.parse_type()?);
+			self.match_token(&[Token::Semicolon])?;
+			self.scan_next()?;
+			let len = self.parse_array_len()?;
+			self.match_token(&[Token::RightBracket])?;
+			self.scan_next()?;
+
+			Type::Array { element_type, len }
+		} else {
+			let Identifier::Symbol(type_name) = self.match_identifier()?;
+			self.scan_next()?;
+
+			Type::Named { type_name }
+		};
 		while self.match_token(&[Token::Asterisk]).is_ok() {
 			self.scan_next()?;
 			res = Type::Pointer { pointee_type: Box::new(res) };
@@ -98,21 +249,35 @@ impl Parser {
 		Ok(res)
 	}
 
-	// Parse a global statement (function for now)
+	// Parse the length of an array type or repeat expression, which must be an integer literal
+	fn parse_array_len(&mut self) -> Result<usize> {
+		match self.current_token.clone() {
+			Some(Token::Literal(Literal::Integer(len))) => {
+				self.scan_next()?;
+				Ok(len as usize)
+			},
+			_ => Err(Error::InvalidArrayLength.at(&self.current_span)),
+		}
+	}
+
+	// Parse a global statement, which is a function, a struct or an enum
 	pub fn parse_global_statement(&mut self) -> Result<Option<ASTNode>> {
 		if self.match_token(&[Token::EndOfFile]).is_ok() {
 			return Ok(None);
 		}
 
 		// Should follow 'fn <name>(<param 1>, <param 2>, ...) { <body_block> }
-		self.match_token(&[Token::Function])?;
+		let start = self.current_span.clone();
+		let keyword = self.match_token(&[Token::Function, Token::Struct, Token::Enum])?;
 		self.scan_next()?;
+		match keyword {
+			Token::Struct => return self.parse_struct_definition(&start).map(Some),
+			Token::Enum => return self.parse_enum_definition(&start).map(Some),
+			_ => {},
+		}
 
 		self.expect_identifier(Identifier::Symbol("".to_string()))?;
-		let name = match self.match_identifier()? {
-			Identifier::Symbol(s) => s,
-			_ => "".to_string(),
-		};
+		let Identifier::Symbol(name) = self.match_identifier()?;
 		self.scan_next()?;
 
 		self.match_token(&[Token::LeftParen])?;
@@ -122,23 +287,22 @@ impl Parser {
 
 		// Parse parameters until right parenthesis is met; don't allow trailing comma
 		while self.expect_identifier(Identifier::Symbol("".to_string())).is_ok() {
-			let id = self.match_identifier()?;
+			let param_start = self.current_span.clone();
+			let Identifier::Symbol(name) = self.match_identifier()?;
 			self.scan_next()?;
 
 			// Param type is required
 			self.match_token(&[Token::Colon])?;
 			self.scan_next()?;
 			let param_type = self.parse_type()?;
+			let span = self.span_from(&param_start);
 
-			if !self.match_token(&[Token::RightParen]).is_ok() {
+			if self.match_token(&[Token::RightParen]).is_err() {
 				self.match_token(&[Token::Comma])?;
 				self.scan_next()?;
 			}
 
-			// Add id to parameter list (guaranteed to run)
-			if let Identifier::Symbol(s) = id {
-				param_list.push(FunctionParameter { name: s, param_type });
-			}
+			param_list.push(FunctionParameter { name, param_type, span });
 		}
 
 		// Should be a right parenthesis
@@ -146,17 +310,103 @@ impl Parser {
 		self.scan_next()?;
 
 		// Return type should be specified; if not, classify it as return void
-		let return_type: Type;
-		if self.match_token(&[Token::Arrow]).is_ok() {
+		let return_type = if self.match_token(&[Token::Arrow]).is_ok() {
 			self.scan_next()?;
-			return_type = self.parse_type()?;
+			self.parse_type()?
 		} else {
-			return_type = Type::Void;
-		}
+			Type::Void
+		};
 
+		let signature_span = self.span_from(&start);
 		let body_block: Vec<ASTNode> = self.parse_block_statement()?;
 
-		Ok(Some(ASTNode::FunctionDefinition { name, parameters: param_list, body_block, return_type }))
+		Ok(Some(ASTNode::FunctionDefinition { name, parameters: param_list, body_block, return_type, signature_span, span: self.span_from(&start) }))
+	}
+
+	// Parse the rest of 'struct <name> { <field>: <type>, ... }' after the keyword.
+	// Fields are usually written one per line, so a trailing comma is allowed.
+	fn parse_struct_definition(&mut self, start: &Span) -> Result<ASTNode> {
+		let Identifier::Symbol(name) = self.match_identifier()?;
+		self.scan_next()?;
+
+		self.match_token(&[Token::LeftCurly])?;
+		self.scan_next()?;
+
+		let mut fields = Vec::new();
+		while self.match_token(&[Token::RightCurly]).is_err() {
+			let field_start = self.current_span.clone();
+			let Identifier::Symbol(field) = self.match_identifier()?;
+			self.scan_next()?;
+
+			self.match_token(&[Token::Colon])?;
+			self.scan_next()?;
+			let field_type = self.parse_type()?;
+			fields.push(StructField { name: field, field_type, span: self.span_from(&field_start) });
+
+			if self.match_token(&[Token::Comma, Token::RightCurly])? == Token::Comma {
+				self.scan_next()?;
+			}
+		}
+		self.scan_next()?;
+
+		Ok(ASTNode::StructDefinition { name, fields, span: self.span_from(start) })
+	}
+
+	// Parse the rest of 'enum <name> { <variant>, <variant> = <discriminant>, ... }' after the keyword.
+	// Discriminants are integer literals, which may be negative; a trailing comma is allowed.
+	fn parse_enum_definition(&mut self, start: &Span) -> Result<ASTNode> {
+		let Identifier::Symbol(name) = self.match_identifier()?;
+		self.scan_next()?;
+
+		self.match_token(&[Token::LeftCurly])?;
+		self.scan_next()?;
+
+		let mut variants = Vec::new();
+		while self.match_token(&[Token::RightCurly]).is_err() {
+			let variant_start = self.current_span.clone();
+			let Identifier::Symbol(variant) = self.match_identifier()?;
+			self.scan_next()?;
+
+			let discriminant = if self.match_token(&[Token::Equals]).is_ok() {
+				self.scan_next()?;
+				Some(self.parse_discriminant()?)
+			} else {
+				None
+			};
+			variants.push(EnumVariant { name: variant, discriminant, span: self.span_from(&variant_start) });
+
+			if self.match_token(&[Token::Comma, Token::RightCurly])? == Token::Comma {
+				self.scan_next()?;
+			}
+		}
+		self.scan_next()?;
+
+		Ok(ASTNode::EnumDefinition { name, variants, span: self.span_from(start) })
+	}
+
+	// Parse an integer literal with an optional '-'
+	fn parse_discriminant(&mut self) -> Result<i128> {
+		let negative = self.match_token(&[Token::Minus]).is_ok();
+		if negative {
+			self.scan_next()?;
+		}
+
+		match self.current_token {
+			Some(Token::Literal(Literal::Integer(value))) => {
+				self.scan_next()?;
+				Ok(if negative { -i128::from(value) } else { value.into() })
+			},
+			_ => Err(Error::LiteralExpected { received: self.current_token.clone().unwrap_or(Token::None) }.at(&self.current_span)),
+		}
+	}
+
+	// Parse the condition of an if or while, which is followed by a block instead of a struct literal
+	fn parse_condition(&mut self) -> Result<ASTNode> {
+		let struct_literals = std::mem::replace(&mut self.struct_literals, false);
+		let condition = self.parse_binary_operation(0);
+		self.struct_literals = struct_literals;
+
+		condition
 	}
 
 	// Parse a statement, which for now contains an identifier followed by a binary expression followed by a semicolon
@@ -168,76 +418,82 @@ impl Parser {
 
 		// Statement should follow the pattern "<identifier> <binary_expr> ;"
 		let token = self.current_token.clone().unwrap();
+		let start = self.current_span.clone();
 
 		Ok(Some(match token {
-			Token::Print => {
+			Token::Print | Token::Println => {
 				self.scan_next()?;
-				Ok(ASTNode::Print {
-					expr: {
-						let b = Box::new(self.parse_binary_operation(0)?);
-						self.match_token(&[Token::Semicolon])?;
-						self.scan_next()?;
+				// A string literal right after print is a format string: 'print "<format>", <arg>, ...;'
+				if let Some(Token::Literal(Literal::String(format))) = self.current_token.clone() {
+					return self.parse_formatted_print(format, token == Token::Println, &start).map(Some);
+				}
 
-						b
-					}
-				})
+				let expr = Box::new(self.parse_binary_operation(0)?);
+				self.match_token(&[Token::Semicolon])?;
+				self.scan_next()?;
+
+				Ok(ASTNode::Print { expr, span: self.span_from(&start) })
 			},
 			Token::Let => {
 				self.scan_next()?;
 				// Let should be formatted as either 'let <symbol> = <value>;' or 'let <symbol>;'
-				let id = self.match_identifier()?;
+				let Identifier::Symbol(symbol) = self.match_identifier()?;
+				let name_span = self.current_span.clone();
+				self.scan_next()?;
+
+				let after_id = self.match_token(&[Token::Equals, Token::Semicolon, Token::Colon])?;
 				self.scan_next()?;
 
-				match id {
-					Identifier::Symbol(symbol) => {
-						let after_id = self.match_token(&[Token::Equals, Token::Semicolon, Token::Colon])?;
+				// If eq_or_semi is Equals, assigment should occur in the same line; else, statement ends on semicolon
+				match after_id {
+					Token::Equals => {
+						let val = Some(Box::new(self.parse_binary_operation(0)?));
+						self.match_token(&[Token::Semicolon])?;
+						self.scan_next()?;
+						Ok(ASTNode::Let {
+							name: symbol,
+							name_span,
+							val_type: None,
+							type_span: None,
+							value: val,
+							span: self.span_from(&start),
+						})
+					},
+					Token::Colon => {
+						let type_start = self.current_span.clone();
+						let val_type = self.parse_type()?;
+						let type_span = Some(self.span_from(&type_start));
+						let semi_or_eq = self.match_token(&[Token::Semicolon, Token::Equals])?;
 						self.scan_next()?;
 
-						// If eq_or_semi is Equals, assigment should occur in the same line; else, statement ends on semicolon
-						match after_id {
+						match semi_or_eq {
 							Token::Equals => {
 								let val = Some(Box::new(self.parse_binary_operation(0)?));
 								self.match_token(&[Token::Semicolon])?;
 								self.scan_next()?;
+
 								Ok(ASTNode::Let {
 									name: symbol,
-									val_type: None,
-									value: val
+									name_span,
+									val_type: Some(val_type),
+									type_span,
+									value: val,
+									span: self.span_from(&start),
 								})
 							},
-							Token::Colon => {
-								let val_type = self.parse_type()?;
-								let semi_or_eq = self.match_token(&[Token::Semicolon, Token::Equals])?;
-								self.scan_next()?;
-
-								match semi_or_eq {
-									Token::Equals => {
-										let val = Some(Box::new(self.parse_binary_operation(0)?));
-										self.match_token(&[Token::Semicolon])?;
-										self.scan_next()?;
-
-										Ok(ASTNode::Let {
-											name: symbol,
-											val_type: Some(val_type),
-											value: val,
-										})
-									},
-									_ => {
-										Ok(ASTNode::Let { name: symbol, val_type: Some(val_type), value: None })
-									}
-								}
+							_ => {
+								Ok(ASTNode::Let { name: symbol, name_span, val_type: Some(val_type), type_span, value: None, span: self.span_from(&start) })
 							}
-							_ => Ok(ASTNode::Let { name: symbol, val_type: None, value: None })
 						}
-					},
-					_ => Err(Error::InvalidIdentifier { expected: [Identifier::Symbol("".to_string())].to_vec(), received: id })
+					}
+					_ => Ok(ASTNode::Let { name: symbol, name_span, val_type: None, type_span: None, value: None, span: self.span_from(&start) })
 				}
 			},
 			Token::If => {
 				self.scan_next()?;
 				// Follows 'if <expr> <block>'
 				// Should get a boolean expression after if;
-				let expr = Box::new(self.parse_binary_operation(0)?);
+				let expr = Box::new(self.parse_condition()?);
 
 				// Parse a block statement and error if there isn't one
 				let block = self.parse_block_statement()?;
@@ -248,33 +504,35 @@ impl Parser {
 
 				let else_block: Option<Vec<ASTNode>> = if is_else { Some(self.parse_block_statement()?) } else { None };
 
-				Ok(ASTNode::If { expr, block, else_block })
+				Ok(ASTNode::If { expr, block, else_block, span: self.span_from(&start) })
 			},
 			Token::While => {
 				self.scan_next()?;
 				// Follows 'while <expr> <block>'
 				// Expecting boolean expression after keyword
-				let expr = Box::new(self.parse_binary_operation(0)?);
+				let expr = Box::new(self.parse_condition()?);
 
 				// Parse a block statement and error if there isn't one
 				let block = self.parse_block_statement()?;
 
-				Ok(ASTNode::While { expr, block })
+				Ok(ASTNode::While { expr, block, span: self.span_from(&start) })
 			},
 			Token::Return => {
 				self.scan_next()?;
 				if self.match_token(&[Token::Semicolon]).is_ok() {
 					self.scan_next()?;
-					Ok(ASTNode::Return { return_val: None})
+					Ok(ASTNode::Return { return_val: None, span: self.span_from(&start) })
 				} else {
 					let return_val = Some(Box::new(self.parse_binary_operation(0)?));
+					self.match_token(&[Token::Semicolon])?;
 					self.scan_next()?;
 
-					Ok(ASTNode::Return { return_val })
+					Ok(ASTNode::Return { return_val, span: self.span_from(&start) })
 				}
 			},
 			_ => {
 				let result = self.parse_binary_operation(0)?;
+				self.match_token(&[Token::Semicolon])?;
 				self.scan_next()?;
 
 				Ok(result)
@@ -299,71 +557,203 @@ impl Parser {
 		Ok(arg_list)
 	}
 
+	// Parse the '<field>: <expr>, ...}' of a struct literal; a trailing comma is allowed
+	pub fn parse_field_initializers(&mut self) -> Result<Vec<FieldInitializer>> {
+		let mut fields = Vec::new();
+		while self.match_token(&[Token::RightCurly]).is_err() {
+			let field_start = self.current_span.clone();
+			let Identifier::Symbol(name) = self.match_identifier()?;
+			self.scan_next()?;
+
+			self.match_token(&[Token::Colon])?;
+			self.scan_next()?;
+			let value = self.parse_binary_operation(0)?;
+			fields.push(FieldInitializer { name, value, span: self.span_from(&field_start) });
+
+			if self.match_token(&[Token::Comma, Token::RightCurly])? == Token::Comma {
+				self.scan_next()?;
+			}
+		}
+		self.scan_next()?;
+
+		Ok(fields)
+	}
+
 	// Parse a terminal node, i.e. a node is created with a literal token
 	pub fn parse_terminal_node(&mut self) -> Result<ASTNode> {
 		let Some(token) = self.current_token.clone() else {
-			return Err(Error::LiteralExpected { received: Token::None });
+			return Err(Error::LiteralExpected { received: Token::None }.at(&self.current_span));
 		};
+		let start = self.current_span.clone();
 
 		match token {
 			Token::LeftParen => {
+				// Struct literals can be used in a condition if they are parenthesized
+				self.scan_next()?;
+				let struct_literals = std::mem::replace(&mut self.struct_literals, true);
+				let res = self.parse_binary_operation(0);
+				self.struct_literals = struct_literals;
+				let res = res?;
+				self.match_token(&[Token::RightParen])?;
 				self.scan_next()?;
-				let res = self.parse_binary_operation(0)?;
-				self.scan_next()?; 
 				Ok(res)
 			},
+			Token::LeftBracket => {
+				// Follows '[<expr>, <expr>, ...]' or '[<expr>; <count>]'
+				self.scan_next()?;
+				let first = self.parse_binary_operation(0)?;
+				if self.match_token(&[Token::Semicolon]).is_ok() {
+					self.scan_next()?;
+					let count = self.parse_array_len()?;
+					self.match_token(&[Token::RightBracket])?;
+					self.scan_next()?;
+
+					return Ok(ASTNode::ArrayRepeat { value: Box::new(first), count, span: self.span_from(&start) });
+				}
+
+				let mut elements = vec![first];
+				while self.match_token(&[Token::Comma, Token::RightBracket])? == Token::Comma {
+					self.scan_next()?;
+					elements.push(self.parse_binary_operation(0)?);
+				}
+				self.scan_next()?;
+
+				Ok(ASTNode::ArrayLiteral { elements, span: self.span_from(&start) })
+			},
 			Token::Asterisk => {
 				self.scan_next()?;
-				Ok(ASTNode::Dereference { child: Box::new(self.parse_terminal_node()?) })
+				let child = Box::new(self.parse_postfix_node()?);
+				Ok(ASTNode::Dereference { child, span: self.span_from(&start) })
 			},
 			Token::Ampersand => {
 				self.scan_next()?;
-				Ok(ASTNode::Reference { child: Box::new(self.parse_terminal_node()?) })
+				let child = Box::new(self.parse_postfix_node()?);
+				Ok(ASTNode::Reference { child, span: self.span_from(&start) })
+			},
+			// Prefix operators bind tighter than any binary operator
+			token if token.is_unary() => {
+				self.scan_next()?;
+				let child = Box::new(self.parse_postfix_node()?);
+				Ok(ASTNode::Unary { token, child, span: self.span_from(&start) })
+			},
+			Token::Literal(Literal::Integer(x)) => {
+				self.scan_next()?;
+				Ok(ASTNode::Literal { value: Literal::Integer(x), span: start })
+			},
+			Token::Literal(Literal::Float(x)) => {
+				self.scan_next()?;
+				Ok(ASTNode::Literal { value: Literal::Float(x), span: start })
+			},
+			Token::Literal(Literal::Char(x)) => {
+				self.scan_next()?;
+				Ok(ASTNode::Literal { value: Literal::Char(x), span: start })
+			},
+			Token::Literal(Literal::String(x)) => {
+				self.scan_next()?;
+				Ok(ASTNode::Literal { value: Literal::String(x), span: start })
+			},
+			Token::Literal(Literal::Boolean(x)) => {
+				self.scan_next()?;
+				Ok(ASTNode::Literal { value: Literal::Boolean(x), span: start })
 			},
-			Token::Literal(Literal::Integer(x)) => {self.scan_next()?; Ok(ASTNode::Literal(Literal::Integer(x)))},
 			Token::Literal(Literal::Identifier(Identifier::Symbol(c))) => {
 				self.scan_next()?;
 
-				// If a left parentheses follows, parse a function call
-				if self.match_token(&[Token::LeftParen]).is_ok() {
+				// If :: follows, parse a variant of an enum; if a left parentheses follows, parse a function call
+				if self.match_token(&[Token::Colon2]).is_ok() {
+					self.scan_next()?;
+					let Identifier::Symbol(member) = self.match_identifier()?;
+					self.scan_next()?;
+
+					Ok(ASTNode::Path { type_name: c, member, span: self.span_from(&start) })
+				} else if self.match_token(&[Token::LeftParen]).is_ok() {
 					self.scan_next()?;
 					let arg_list = self.parse_function_args()?;
 
-					Ok(ASTNode::FunctionCall { name: c, args: arg_list })
+					Ok(ASTNode::FunctionCall { name: c, args: arg_list, span: self.span_from(&start) })
+				} else if self.struct_literals && self.match_token(&[Token::LeftCurly]).is_ok() {
+					self.scan_next()?;
+					let fields = self.parse_field_initializers()?;
+
+					Ok(ASTNode::StructLiteral { name: c, fields, span: self.span_from(&start) })
 				} else {
-					Ok(ASTNode::Literal(Literal::Identifier(Identifier::Symbol(c))))
+					Ok(ASTNode::Literal { value: Literal::Identifier(Identifier::Symbol(c)), span: start })
 				}
 			}
-			_ => { Err(Error::LiteralExpected { received: token })}
+			_ => { Err(Error::LiteralExpected { received: token }.at(&start)) }
+		}
+	}
+
+	// Parse a terminal node followed by any number of postfix ++, --, [<index>] and .<field>, which bind tighter than prefix operators
+	pub fn parse_postfix_node(&mut self) -> Result<ASTNode> {
+		let start = self.current_span.clone();
+		let mut node = self.parse_terminal_node()?;
+
+		loop {
+			match self.current_token.clone() {
+				Some(token) if token.is_increment() => {
+					self.scan_next()?;
+					node = ASTNode::Postfix { token, child: Box::new(node), span: self.span_from(&start) };
+				},
+				Some(Token::LeftBracket) => {
+					self.scan_next()?;
+					let index = Box::new(self.parse_binary_operation(0)?);
+					self.match_token(&[Token::RightBracket])?;
+					self.scan_next()?;
+					node = ASTNode::Index { child: Box::new(node), index, span: self.span_from(&start) };
+				},
+				Some(Token::Dot) => {
+					self.scan_next()?;
+					let Identifier::Symbol(field) = self.match_identifier()?;
+					self.scan_next()?;
+					node = ASTNode::FieldAccess { child: Box::new(node), field, span: self.span_from(&start) };
+				},
+				_ => return Ok(node),
+			}
 		}
 	}
 
+	// Parse an operand of a binary operator, which may be cast with 'as <type>'.
+	// Casts bind tighter than binary operators but looser than prefix ones, so -x as u8 is (-x) as u8.
+	pub fn parse_cast_node(&mut self) -> Result<ASTNode> {
+		let start = self.current_span.clone();
+		let mut node = self.parse_postfix_node()?;
+
+		while self.match_token(&[Token::As]).is_ok() {
+			self.scan_next()?;
+			let target_type = self.parse_type()?;
+			node = ASTNode::Cast { child: Box::new(node), target_type, span: self.span_from(&start) };
+		}
+
+		Ok(node)
+	}
+
 	// Get precedence of token or error if not a valid operator
 	pub fn get_precedence(&self, token: &Token) -> Result<u8> {
 		// Search precedence array for token, else invalid token
-		for (_, prec) in OPERATOR_PRECEDENCE.iter().enumerate() {
+		for prec in OPERATOR_PRECEDENCE.iter() {
 			if prec.0 == *token {
 				return Ok(prec.1);
 			}
 		}
-		Err(Error::BinaryOperatorExpected { received: token.clone() })
+		Err(Error::BinaryOperatorExpected { received: token.clone() }.at(&self.current_span))
 	}
 
 	pub fn parse_binary_operation(&mut self, prev: u8) -> Result<ASTNode> {
-		let mut left = self.parse_terminal_node()?;
+		let mut left = self.parse_cast_node()?;
 		let mut right: ASTNode;
 
 		let mut token: Token;
 
 		match &self.current_token {
 			Some(t) => { token = t.clone(); },
-			None => { return Err(Error::BinaryOperatorExpected { received: Token::None }); }
+			None => { return Err(Error::BinaryOperatorExpected { received: Token::None }.at(&self.current_span)); }
 		}
 
-		let expr_finishers = [Token::Semicolon, Token::LeftCurly, Token::RightCurly, Token::RightParen, Token::Comma];
+		let expr_finishers = [Token::Semicolon, Token::LeftCurly, Token::RightCurly, Token::RightParen, Token::RightBracket, Token::Comma];
 
 		if let Token::EndOfFile = token {
-			return Err(Error::InvalidToken { expected: expr_finishers.to_vec(), received: Token::EndOfFile });
+			return Err(Error::InvalidToken { expected: expr_finishers.to_vec(), received: Token::EndOfFile }.at(&self.current_span));
 		} 
 		
 		if self.match_token(&expr_finishers).is_ok() {
@@ -378,7 +768,8 @@ impl Parser {
 			right = self.parse_binary_operation(self.get_precedence(&token)?)?;
 
 			// Join left and right into parent node connected by operator token
-			left = ASTNode::Binary { token: token.clone(), left: Box::new(left), right: Box::new(right) };
+			let span = left.span().to(right.span());
+			left = ASTNode::Binary { token: token.clone(), left: Box::new(left), right: Box::new(right), span };
 
 			// If EOF reached, return the new left
 			if let Some(Token::EndOfFile) = self.current_token {
@@ -391,13 +782,31 @@ impl Parser {
 
 			match &self.current_token {
 				Some(t) => { token = t.clone(); },
-				None => { return Err(Error::BinaryOperatorExpected { received: Token::None }); }
+				None => { return Err(Error::BinaryOperatorExpected { received: Token::None }.at(&self.current_span)); }
 			}
 		}
 
 		Ok(left)
 	}
 
+	// Parse the rest of a print with a format string, which is the current token. println ends the output with a line break.
+	pub fn parse_formatted_print(&mut self, format: Vec<u8>, newline: bool, start: &Span) -> Result<ASTNode> {
+		let mut pieces = split_format(&format).map_err(|error| error.at(&self.current_span))?;
+		if newline {
+			pieces.last_mut().expect("there is always a piece after the last placeholder").push(b'\n');
+		}
+		self.scan_next()?;
+
+		let mut args = Vec::new();
+		while self.match_token(&[Token::Comma, Token::Semicolon])? == Token::Comma {
+			self.scan_next()?;
+			args.push(self.parse_binary_operation(0)?);
+		}
+		self.scan_next()?;
+
+		Ok(ASTNode::FormattedPrint { pieces, args, span: self.span_from(start) })
+	}
+
 	pub fn parse_block_statement(&mut self) -> Result<Vec<ASTNode>> {
 		// Follows '{ <statement> <statement> ... }'
 		self.match_token(&[Token::LeftCurly])?;
@@ -405,11 +814,19 @@ impl Parser {
 		let mut statements = Vec::new();
 
 		while self.current_token != Some(Token::RightCurly) {
-			let statement = self.parse_statement()?;
-			if let Some(node) = statement {
-				statements.push(node);
-			} else {
-				return Err(Error::UnexpectedEOF { expected: Token::RightCurly });
+			// A function or type can't start inside a block, so the block must be missing its closing brace
+			if let Ok(token) = self.match_token(&[Token::Function, Token::Struct, Token::Enum]) {
+				return Err(Error::InvalidToken { expected: vec![Token::RightCurly], received: token }.at(&self.current_span));
+			}
+
+			match self.parse_statement() {
+				Ok(Some(node)) => statements.push(node),
+				Ok(None) => return Err(Error::UnexpectedEOF { expected: Token::RightCurly }.at(&self.current_span)),
+				Err(error) => {
+					// Record the error and carry on with the next statement
+					self.record_error(error);
+					self.synchronize_statement();
+				}
 			}
 		}
 
@@ -417,4 +834,26 @@ impl Parser {
 
 		Ok(statements)
 	}
+}
+
+// Split a format string at its {} placeholders. {{ and }} stand for a literal brace; any other brace is an error.
+pub fn split_format(format: &[u8]) -> Result<Vec<Vec<u8>>> {
+	let mut pieces = vec![Vec::new()];
+	let mut bytes = format.iter().peekable();
+	while let Some(&byte) = bytes.next() {
+		match (byte, bytes.peek()) {
+			(b'{', Some(b'}')) => {
+				bytes.next();
+				pieces.push(Vec::new());
+			},
+			(b'{', Some(b'{')) | (b'}', Some(b'}')) => {
+				bytes.next();
+				pieces.last_mut().expect("pieces is never empty").push(byte);
+			},
+			(b'{' | b'}', _) => return Err(Error::InvalidFormatString { format: String::from_utf8_lossy(format).into_owned() }),
+			_ => pieces.last_mut().expect("pieces is never empty").push(byte),
+		}
+	}
+
+	Ok(pieces)
 }
\ No newline at end of file
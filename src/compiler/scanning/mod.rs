@@ -1,69 +1,125 @@
 pub mod token;
+pub mod span;
 
 use token::*;
+use span::Span;
 use utf8_chars::BufReadCharsExt;
 
-use std::fmt::Debug;
+use std::fmt;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{self, BufRead, BufReader, Cursor};
 
 use crate::error::*;
 
-#[derive(Debug)]
+// Input filename that reads from stdin instead, and the name its spans are reported under
+pub const STDIN_FILENAME: &str = "-";
+pub const STDIN_DISPLAY_NAME: &str = "<stdin>";
+
 pub struct Scanner {
-	file: BufReader<File>,
+	reader: Box<dyn BufRead>,
 	put_backs: Vec<char>,
 	filename: String,
+	source: String,
+	offset: usize,
 	line: usize,
+	column: usize,
+	char_start: (usize, usize, usize),
 }
 
 impl Scanner {
-	pub fn new(filename: String, file: BufReader<File>) -> Self {
+	// Scan source read from reader; filename is only used to report spans
+	pub fn new(filename: String, reader: impl BufRead + 'static) -> Self {
 		Self {
-			file,
+			reader: Box::new(reader),
 			filename,
-			put_backs: [].to_vec(),
+			put_backs: Vec::new(),
+			source: String::new(),
+			offset: 0,
 			line: 1,
+			column: 1,
+			char_start: (0, 1, 1),
 		}
 	}
 
-	// Open file with given filename
+	// Open file with given filename, or stdin if the filename is '-'
 	pub fn open_file(filename: String) -> Result<Self> {
+		if filename == STDIN_FILENAME {
+			return Ok(Self::new(STDIN_DISPLAY_NAME.to_owned(), io::stdin().lock()));
+		}
+
 		File::open(&filename)
 			.map(|file| Self::new(filename, BufReader::new(file)))
 			.map_err(|cause| Error::FileOpenError { cause })
 	}
 
-	// Put character in put backs
-	pub fn put_back(&mut self, c: char) -> () {
+	// Scan source already in memory
+	pub fn from_str(filename: String, source: &str) -> Self {
+		Self::new(filename, Cursor::new(source.to_owned()))
+	}
+
+	// Put character in put backs and move position back to where it was read
+	pub fn put_back(&mut self, c: char) {
 		self.put_backs.push(c);
+		self.offset -= c.len_utf8();
+
+		if c == '\n' {
+			// Column is the distance from the start of the previous line
+			let line_start = self.source[..self.offset].rfind('\n').map_or(0, |i| i + 1);
+			self.line -= 1;
+			self.column = self.source[line_start..self.offset].chars().count() + 1;
+		} else {
+			self.column -= 1;
+		}
 	}
 
 	// Get next character in reader
 	pub fn next_char(&mut self) -> Result<Option<char>> {
 		// If there are any characters on put back, return the top
-		if let Some(c) = self.put_backs.pop() {
-			return Ok(Some(c));
-		}
+		let next = match self.put_backs.pop() {
+			Some(c) => Some(c),
+			None => {
+				let next = self.reader.read_char()
+					.map_err(|cause| Error::FileReadError { cause })?;
+
+				if let Some(c) = next {
+					self.source.push(c);
+				}
+
+				next
+			}
+		};
 
-		let next = self.file.read_char()
-			.map_err(|cause| Error::FileReadError { cause });
+		if let Some(c) = next {
+			self.char_start = (self.offset, self.line, self.column);
+			self.offset += c.len_utf8();
 
-		if let Ok(Some(c)) = next {
 			// If next is line break, add one to line counter
 			if c == '\n' {
 				self.line += 1;
+				self.column = 1;
+			} else {
+				self.column += 1;
 			}
-
-			return Ok(Some(c));
 		}
-		
-		Ok(None)
+
+		Ok(next)
+	}
+
+	// Span from the start of the most recently read character to the current position
+	pub fn span_from(&self, start: (usize, usize, usize)) -> Span {
+		let (offset, line, column) = start;
+
+		Span::new(self.filename.clone(), line, column, offset, self.offset)
+	}
+
+	// Span of the position the scanner is currently at
+	pub fn current_span(&self) -> Span {
+		self.span_from((self.offset, self.line, self.column))
 	}
 
 	// Skip over all whitespace and return next char
 	pub fn skip_whitespace(&mut self) -> Result<Option<char>> {
-		while let Ok(Some(c)) = self.next_char() {
+		while let Some(c) = self.next_char()? {
 			if !c.is_whitespace() {
 				self.put_back(c);
 				break;
@@ -96,12 +152,38 @@ impl Scanner {
 		}
 	}
 
-	// Scan in next token and return result
-	pub fn scan(&mut self) -> Result<Option<Token>> {
+	// Scan a block comment, whose opening has already been read; comments nest, so each /* needs its own */
+	pub fn scan_block_comment(&mut self, opening: Span) -> Result<()> {
+		let mut depth = 1;
+		let mut prev = ' ';
+
+		while depth > 0 {
+			let Some(c) = self.next_char()? else {
+				return Err(Error::UnterminatedComment.at(&opening));
+			};
+
+			// A character can't be part of both an opening and a closing, e.g. in /*/
+			if prev == '/' && c == '*' {
+				depth += 1;
+				prev = ' ';
+			} else if prev == '*' && c == '/' {
+				depth -= 1;
+				prev = ' ';
+			} else {
+				prev = c;
+			}
+		}
+
+		Ok(())
+	}
+
+	// Scan in next token and return result along with where it was found
+	pub fn scan(&mut self) -> Result<Option<(Token, Span)>> {
 		let next = self.skip_whitespace()?;
+		let start = self.char_start;
 
-		if let Some(mut c) = next {
-			// Check if c is a /, if it is, check if next character is a slash; if it is, scan next character until new line is reached
+		if let Some(c) = next {
+			// Check if c is a /, if it is, check if next character is a slash or asterisk; if it is, skip the comment
 			if c == '/' {
 				let next = self.next_char()?;
 				if let Some(next_char) = next {
@@ -109,7 +191,12 @@ impl Scanner {
 						// Line comment
 						self.scan_line_comment()?;
 
-						return Ok(self.scan()?);
+						return self.scan();
+					} else if next_char == '*' {
+						// Block comment
+						self.scan_block_comment(self.span_from(start))?;
+
+						return self.scan();
 					} else {
 						self.put_back(next_char);
 					}
@@ -118,97 +205,168 @@ impl Scanner {
 
 			// Check if c is the start of a literal
 			if c.is_numeric() {
-				let num = self.scan_integer_literal(c)?;
+				let literal = self.scan_numeric_literal(c).map_err(|error| error.at(&self.span_from(start)))?;
+
+				return Ok(Some((Token::Literal(literal), self.span_from(start))));
+			}
+
+			if c == '\'' {
+				let literal = self.scan_char_literal().map_err(|error| error.at(&self.span_from(start)))?;
+
+				return Ok(Some((Token::Literal(literal), self.span_from(start))));
+			}
+
+			if c == '"' {
+				let literal = self.scan_string_literal().map_err(|error| error.at(&self.span_from(start)))?;
 
-				return Ok(Some(Token::Literal(Literal::Integer(num))));
+				return Ok(Some((Token::Literal(literal), self.span_from(start))));
 			}
 
 			// Check if c is start of an identifier
 			if c.is_alphabetic() {
 				let identifier = self.scan_identifier(c)?;
 
-				for (_, id) in KEYWORD_TOKENS.iter().enumerate() {
+				for id in KEYWORD_TOKENS.iter() {
 					if identifier.eq(id.0) {
-						return Ok(Some(id.1.clone()));
+						return Ok(Some((id.1.clone(), self.span_from(start))));
 					}
 				}
 
-				return Ok(Some(Token::Literal(Literal::Identifier(Identifier::Symbol(identifier)))));
+				return Ok(Some((Token::Literal(Literal::Identifier(Identifier::Symbol(identifier))), self.span_from(start))));
 			}
 
-			// Generate possible symbols that c represents
-			let mut remaining_symbols: Vec<&(&str, Token)> = Vec::new();
-			let mut curr: String = String::from(c);
-			
-			for (_, symbol) in TOKEN_SYMBOLS.iter().enumerate() {
-				if symbol.0.chars().nth(0).unwrap() == c {
-					remaining_symbols.push(symbol);
-				}
+			// If no symbol starts with c, token is invalid
+			if !TOKEN_SYMBOLS.iter().any(|symbol| symbol.0.starts_with(c)) {
+				return Ok(Some((Token::None, self.span_from(start))));
 			}
 
-			while remaining_symbols.len() >= 1 {
-				// If current symbol matches only remaining, don't let it get removed due to mismatch
-				if remaining_symbols.len() == 1 && remaining_symbols[0].0 == curr {
-					break;
-				}
+			// Read the longest run of characters that could still be a symbol, so that e.g. '!(' is '!' followed by '('
+			let mut curr: String = String::from(c);
+			while let Some(next) = self.next_char()? {
+				let mut extended = curr.clone();
+				extended.push(next);
 
-				// If character is alphanumeric/whitespace or EOF reached, stop reading symbols
-				if let Some(next) = self.next_char()? {
-					c = next;
+				if TOKEN_SYMBOLS.iter().any(|symbol| symbol.0.starts_with(&extended)) {
+					curr = extended;
 				} else {
+					self.put_back(next);
 					break;
 				}
-
-				if c.is_alphanumeric() || c.is_whitespace() {
-					self.put_back(c);
-					break;
-				} else {
-					curr.push(c);
-
-					// Remove symbols that don't match
-					remaining_symbols.retain(|symbol| symbol.0.starts_with(&curr));
-				}
 			}
 
-			// If possible symbols is empty, token is invalid
-			if remaining_symbols.len() == 0 {
-				Ok(Some(Token::None))
-			} else {
-				for (_, symbol) in remaining_symbols.iter().enumerate() {
-					if symbol.0 == curr {
-						return Ok(Some(symbol.1.clone()));
-					}
-				}
-
-				Err(Error::UnknownToken { received: curr })
+			match TOKEN_SYMBOLS.iter().find(|symbol| symbol.0 == curr) {
+				Some(symbol) => Ok(Some((symbol.1.clone(), self.span_from(start)))),
+				None => Err(Error::UnknownToken { received: curr }.at(&self.span_from(start))),
 			}
 		} else {
-			Ok(Some(Token::EndOfFile))
+			Ok(Some((Token::EndOfFile, self.current_span())))
 		}
 	}
 
-	// Scan in integer literal
-	pub fn scan_integer_literal(&mut self, mut c: char) -> Result<i64> {
-		let mut res: i64 = 0;
-		while c.is_numeric() {
-			res = res * 10 + (c as i64 - ('0' as i64));
-			
+	// Scan in numeric literal; 0x, 0o and 0b prefixes change the base and '_' can separate digits.
+	// Decimal literals with a fraction or an exponent, e.g. 3.14 or 1e-9, are floats.
+	pub fn scan_numeric_literal(&mut self, mut c: char) -> Result<Literal> {
+		// Read everything that could belong to the literal, so that e.g. 12ab is reported as a whole
+		let mut literal = String::new();
+		loop {
+			literal.push(c);
+			let decimal = !matches!(literal.get(..2), Some("0x" | "0o" | "0b"));
+
 			match self.next_char()? {
-				Some(next) => {c = next;},
-				None => return Ok(res)
+				Some(next) if next.is_alphanumeric() || next == '_' => {c = next;},
+				// The sign of an exponent
+				Some(next @ ('+' | '-')) if decimal && literal.ends_with(['e', 'E']) => {c = next;},
+				// A '.' is only part of the literal if a digit follows, so 1.x isn't read as a float
+				Some('.') if decimal && !literal.contains(['.', 'e', 'E']) => {
+					match self.next_char()? {
+						Some(digit) if digit.is_ascii_digit() => {
+							literal.push('.');
+							c = digit;
+						},
+						Some(other) => {
+							self.put_back(other);
+							self.put_back('.');
+							break;
+						},
+						None => {
+							self.put_back('.');
+							break;
+						},
+					}
+				},
+				Some(next) => {
+					self.put_back(next);
+					break;
+				},
+				None => break,
 			}
 		}
 
-		self.put_back(c);
+		let (radix, digits) = match literal.get(..2) {
+			Some("0x") => (16, &literal[2..]),
+			Some("0o") => (8, &literal[2..]),
+			Some("0b") => (2, &literal[2..]),
+			_ => (10, literal.as_str()),
+		};
+
+		if radix == 10 && digits.contains(['.', 'e', 'E']) {
+			return digits.replace('_', "").parse()
+				.map(Literal::Float)
+				.map_err(|cause| Error::FloatParseError { literal: literal.clone(), cause });
+		}
 
-		Ok(res)
+		u64::from_str_radix(&digits.replace('_', ""), radix)
+			.map(Literal::Integer)
+			.map_err(|cause| Error::StringParseError { literal: literal.clone(), cause })
+	}
+
+	// Scan in a character literal after its opening quote. It must be one byte once escapes are decoded.
+	pub fn scan_char_literal(&mut self) -> Result<Literal> {
+		let text = self.scan_quoted('\'')?;
+
+		match unescape(&text)?.as_slice() {
+			[byte] => Ok(Literal::Char(*byte)),
+			_ => Err(Error::InvalidCharLiteral { literal: format!("'{text}'") }),
+		}
+	}
+
+	// Scan a string literal after its opening quote
+	pub fn scan_string_literal(&mut self) -> Result<Literal> {
+		let text = self.scan_quoted('"')?;
+
+		Ok(Literal::String(unescape(&text)?))
+	}
+
+	// Read the raw text up to a closing quote, which may be escaped with a backslash.
+	// The literal must end on the line it starts on.
+	fn scan_quoted(&mut self, quote: char) -> Result<String> {
+		let mut text = String::new();
+		loop {
+			match self.next_char()? {
+				Some(c) if c == quote => return Ok(text),
+				Some('\\') => {
+					text.push('\\');
+					match self.next_char()? {
+						Some('\n') => self.put_back('\n'),
+						Some(escaped) => text.push(escaped),
+						None => {},
+					}
+				},
+				Some('\n') => {
+					self.put_back('\n');
+					return Err(Error::UnterminatedLiteral { literal: format!("{quote}{text}") });
+				},
+				Some(c) => text.push(c),
+				None => return Err(Error::UnterminatedLiteral { literal: format!("{quote}{text}") }),
+			}
+		}
 	}
 
 	// Scan in identifier
 	pub fn scan_identifier(&mut self, mut c: char) -> Result<String> {
 		let mut res: String = String::from("");
 		while c.is_alphanumeric() || c == '_' {
-			res.insert(res.len(), c);
+			res.push(c);
 
 			match self.next_char()? {
 				Some(next) => {c = next;},
@@ -221,10 +379,6 @@ impl Scanner {
 		Ok(res)
 	}
 
-	pub fn file(&self) -> &BufReader<File> {
-		&self.file
-	}
-
 	pub fn put_backs(&self) -> &[char] {
 		&self.put_backs
 	}
@@ -232,4 +386,56 @@ impl Scanner {
 	pub fn filename(&self) -> &String {
 		&self.filename
 	}
+
+	pub fn source(&self) -> &str {
+		&self.source
+	}
+}
+
+impl fmt::Debug for Scanner {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Scanner")
+			.field("put_backs", &self.put_backs)
+			.field("filename", &self.filename)
+			.field("offset", &self.offset)
+			.field("line", &self.line)
+			.field("column", &self.column)
+			.finish_non_exhaustive()
+	}
+}
+
+// Decode the escapes of a character or string literal into the bytes it stands for.
+// Supported are \n, \t, \r, \0, \\, \', \" and \xHH with two hex digits.
+pub fn unescape(text: &str) -> Result<Vec<u8>> {
+	let mut bytes = Vec::new();
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			let mut buffer = [0; 4];
+			bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+			continue;
+		}
+
+		let byte = match chars.next() {
+			Some('n') => b'\n',
+			Some('t') => b'\t',
+			Some('r') => b'\r',
+			Some('0') => b'\0',
+			Some('\\') => b'\\',
+			Some('\'') => b'\'',
+			Some('"') => b'"',
+			Some('x') => {
+				let digits: String = chars.by_ref().take(2).collect();
+				match u8::from_str_radix(&digits, 16) {
+					Ok(byte) if digits.len() == 2 && !digits.starts_with('+') => byte,
+					_ => return Err(Error::InvalidEscape { sequence: format!("\\x{digits}") }),
+				}
+			},
+			Some(other) => return Err(Error::InvalidEscape { sequence: format!("\\{other}") }),
+			None => return Err(Error::InvalidEscape { sequence: String::from("\\") }),
+		};
+		bytes.push(byte);
+	}
+
+	Ok(bytes)
 }
\ No newline at end of file
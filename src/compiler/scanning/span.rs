@@ -0,0 +1,60 @@
+use std::fmt;
+
+// Location of a piece of source text; start and end are byte offsets into the file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+	file: String,
+	line: usize,
+	column: usize,
+	start: usize,
+	end: usize,
+}
+
+impl Span {
+	pub fn new(file: String, line: usize, column: usize, start: usize, end: usize) -> Self {
+		Self {
+			file,
+			line,
+			column,
+			start,
+			end,
+		}
+	}
+
+	// Create a span that starts at self and ends where other ends
+	pub fn to(&self, other: &Span) -> Span {
+		Span {
+			file: self.file.clone(),
+			line: self.line,
+			column: self.column,
+			start: self.start,
+			end: other.end.max(self.end),
+		}
+	}
+
+	pub fn file(&self) -> &str {
+		&self.file
+	}
+
+	pub fn line(&self) -> usize {
+		self.line
+	}
+
+	pub fn column(&self) -> usize {
+		self.column
+	}
+
+	pub fn start(&self) -> usize {
+		self.start
+	}
+
+	pub fn end(&self) -> usize {
+		self.end
+	}
+}
+
+impl fmt::Display for Span {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}:{}:{}", self.file, self.line, self.column)
+	}
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+use crate::generating::llvm::RegisterFormat;
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Token {
 	EndOfFile,
 	None,
@@ -9,14 +11,35 @@ pub enum Token {
 	RightCurly,
 	LeftParen,
 	RightParen,
+	LeftBracket,
+	RightBracket,
 	Plus,
 	Minus,
 	Asterisk,
 	Slash,
+	Percent,
+	Pipe,
+	Caret,
+	LessThan2,
+	GreaterThan2,
 	Semicolon,
 	Comma,
 	Colon,
+	Colon2,
+	Dot,
 	Equals,
+	PlusEqual,
+	MinusEqual,
+	AsteriskEqual,
+	SlashEqual,
+	PercentEqual,
+	AmpersandEqual,
+	PipeEqual,
+	CaretEqual,
+	LessThan2Equal,
+	GreaterThan2Equal,
+	Plus2,
+	Minus2,
 	Equals2,
 	ExclamationEqual,
 	LessThan,
@@ -25,38 +48,89 @@ pub enum Token {
 	GreaterThanEqual,
 	Arrow,
 	Ampersand,
+	Ampersand2,
+	Pipe2,
+	Exclamation,
+	Tilde,
 	Let,
 	Print,
+	Println,
 	If,
 	Else,
 	While,
 	Function,
+	Struct,
+	Enum,
 	Return,
+	As,
 }
 
 impl Token {
 	pub fn is_rl_associativity(&self) -> bool {
+		matches!(self, Token::Equals) || self.compound_operator().is_some()
+	}
+
+	// The binary operator a compound assignment applies, e.g. + for +=
+	pub fn compound_operator(&self) -> Option<Token> {
 		match self {
-			Token::Equals => true,
-			_ => false,
+			Token::PlusEqual => Some(Token::Plus),
+			Token::MinusEqual => Some(Token::Minus),
+			Token::AsteriskEqual => Some(Token::Asterisk),
+			Token::SlashEqual => Some(Token::Slash),
+			Token::PercentEqual => Some(Token::Percent),
+			Token::AmpersandEqual => Some(Token::Ampersand),
+			Token::PipeEqual => Some(Token::Pipe),
+			Token::CaretEqual => Some(Token::Caret),
+			Token::LessThan2Equal => Some(Token::LessThan2),
+			Token::GreaterThan2Equal => Some(Token::GreaterThan2),
+			_ => None,
 		}
 	}
 
+	// Whether the token can be used as a prefix operator, other than * and &
+	pub fn is_unary(&self) -> bool {
+		matches!(self, Token::Minus | Token::Exclamation | Token::Tilde) || self.is_increment()
+	}
+
+	// Whether the token is ++ or --, which can be used both before and after an lvalue
+	pub fn is_increment(&self) -> bool {
+		matches!(self, Token::Plus2 | Token::Minus2)
+	}
+
+	pub fn is_arithmetic(&self) -> bool {
+		matches!(self, Token::Plus | Token::Minus | Token::Asterisk | Token::Slash | Token::Percent)
+	}
+
+	// Whether the token is a binary bitwise or shift operator
+	pub fn is_bitwise(&self) -> bool {
+		matches!(self, Token::Ampersand | Token::Pipe | Token::Caret | Token::LessThan2 | Token::GreaterThan2)
+	}
+
+	pub fn is_logical(&self) -> bool {
+		matches!(self, Token::Ampersand2 | Token::Pipe2)
+	}
+
 	pub fn is_comparison(&self) -> bool {
-		match self {
-			Token::Equals2 | Token::ExclamationEqual | Token::LessThan | Token::LessThanEqual | Token::GreaterThan | Token::GreaterThanEqual => true,
-			_ => false,
-		}
+		matches!(self, Token::Equals2 | Token::ExclamationEqual | Token::LessThan | Token::LessThanEqual | Token::GreaterThan | Token::GreaterThanEqual)
 	}
 
-	pub fn get_pnemonic(&self) -> String {
+	// icmp condition for a comparison of signed or unsigned operands, or the fcmp one for floats.
+	// Float comparisons are false if either side is NaN, except for != which is true.
+	pub fn get_pnemonic(&self, format: &RegisterFormat) -> String {
+		let sign = match format {
+			RegisterFormat::Float { .. } => "o",
+			_ if format.is_signed() => "s",
+			_ => "u",
+		};
 		match self {
+			Token::Equals2 if format.is_float() => String::from("oeq"),
+			Token::ExclamationEqual if format.is_float() => String::from("une"),
 			Token::Equals2 => String::from("eq"),
 			Token::ExclamationEqual => String::from("ne"),
-			Token::LessThan => String::from("slt"),
-			Token::LessThanEqual => String::from("sle"),
-			Token::GreaterThan => String::from("sgt"),
-			Token::GreaterThanEqual => String::from("sge"),
+			Token::LessThan => format!("{sign}lt"),
+			Token::LessThanEqual => format!("{sign}le"),
+			Token::GreaterThan => format!("{sign}gt"),
+			Token::GreaterThanEqual => format!("{sign}ge"),
 			_ => String::from(""),
 		}
 	}
@@ -72,15 +146,40 @@ impl fmt::Display for Token {
 			Token::RightCurly => write!(f, "}}"),
 			Token::LeftParen => write!(f, "("),
 			Token::RightParen => write!(f, ")"),
+			Token::LeftBracket => write!(f, "["),
+			Token::RightBracket => write!(f, "]"),
 			Token::Plus => write!(f, "+"),
 			Token::Minus => write!(f, "-"),
 			Token::Asterisk => write!(f, "*"),
 			Token::Slash => write!(f, "/"),
+			Token::Percent => write!(f, "%"),
+			Token::Pipe => write!(f, "|"),
+			Token::Caret => write!(f, "^"),
+			Token::LessThan2 => write!(f, "<<"),
+			Token::GreaterThan2 => write!(f, ">>"),
 			Token::Semicolon => write!(f, ";"),
 			Token::Ampersand => write!(f, "&"),
+			Token::Ampersand2 => write!(f, "&&"),
+			Token::Pipe2 => write!(f, "||"),
+			Token::Exclamation => write!(f, "!"),
+			Token::Tilde => write!(f, "~"),
 			Token::Colon => write!(f, ":"),
+			Token::Colon2 => write!(f, "::"),
+			Token::Dot => write!(f, "."),
 			Token::Comma => write!(f, ","),
 			Token::Equals => write!(f, "="),
+			Token::PlusEqual => write!(f, "+="),
+			Token::MinusEqual => write!(f, "-="),
+			Token::AsteriskEqual => write!(f, "*="),
+			Token::SlashEqual => write!(f, "/="),
+			Token::PercentEqual => write!(f, "%="),
+			Token::AmpersandEqual => write!(f, "&="),
+			Token::PipeEqual => write!(f, "|="),
+			Token::CaretEqual => write!(f, "^="),
+			Token::LessThan2Equal => write!(f, "<<="),
+			Token::GreaterThan2Equal => write!(f, ">>="),
+			Token::Plus2 => write!(f, "++"),
+			Token::Minus2 => write!(f, "--"),
 			Token::Equals2 => write!(f, "=="),
 			Token::ExclamationEqual => write!(f, "!="),
 			Token::LessThan => write!(f, "<"),
@@ -89,12 +188,16 @@ impl fmt::Display for Token {
 			Token::GreaterThanEqual => write!(f, ">="),
 			Token::Arrow => write!(f, "->"),
 			Token::Print => write!(f, "print"),
+			Token::Println => write!(f, "println"),
 			Token::Let => write!(f, "let"),
 			Token::If => write!(f, "if"),
 			Token::Else => write!(f, "else"),
 			Token::While => write!(f, "while"),
 			Token::Function => write!(f, "fn"),
+			Token::Struct => write!(f, "struct"),
+			Token::Enum => write!(f, "enum"),
 			Token::Return => write!(f, "return"),
+			Token::As => write!(f, "as"),
 		}
 	}
 }
@@ -112,9 +215,15 @@ impl fmt::Display for Identifier {
 	}
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
-	Integer(i64),
+	// Integer literals are never negative; a leading '-' is an operator. Which format they fit in is only known once they are used.
+	Integer(u64),
+	Float(f64),
+	Char(u8),
+	// Bytes of a string literal, without the terminating NUL
+	String(Vec<u8>),
+	Boolean(bool),
 	Identifier(Identifier)
 }
 
@@ -123,14 +232,35 @@ pub const TOKEN_SYMBOLS: &[(&str, Token)] = &[
 	("}", Token::RightCurly),
 	("(", Token::LeftParen),
 	(")", Token::RightParen),
+	("[", Token::LeftBracket),
+	("]", Token::RightBracket),
 	("+", Token::Plus),
 	("-", Token::Minus),
 	("*", Token::Asterisk),
 	("/", Token::Slash),
+	("%", Token::Percent),
+	("|", Token::Pipe),
+	("^", Token::Caret),
+	("<<", Token::LessThan2),
+	(">>", Token::GreaterThan2),
 	(";", Token::Semicolon),
 	(",", Token::Comma),
 	(":", Token::Colon),
+	("::", Token::Colon2),
+	(".", Token::Dot),
 	("=", Token::Equals),
+	("+=", Token::PlusEqual),
+	("-=", Token::MinusEqual),
+	("*=", Token::AsteriskEqual),
+	("/=", Token::SlashEqual),
+	("%=", Token::PercentEqual),
+	("&=", Token::AmpersandEqual),
+	("|=", Token::PipeEqual),
+	("^=", Token::CaretEqual),
+	("<<=", Token::LessThan2Equal),
+	(">>=", Token::GreaterThan2Equal),
+	("++", Token::Plus2),
+	("--", Token::Minus2),
 	("==", Token::Equals2),
 	("!=", Token::ExclamationEqual),
 	("<", Token::LessThan),
@@ -139,14 +269,24 @@ pub const TOKEN_SYMBOLS: &[(&str, Token)] = &[
 	(">=", Token::GreaterThanEqual),
 	("->", Token::Arrow),
 	("&", Token::Ampersand),
+	("&&", Token::Ampersand2),
+	("||", Token::Pipe2),
+	("!", Token::Exclamation),
+	("~", Token::Tilde),
 ];
 
 pub const KEYWORD_TOKENS: &[(&str, Token)] = &[
 	("let", Token::Let),
 	("print", Token::Print),
+	("println", Token::Println),
 	("if", Token::If),
 	("else", Token::Else),
 	("while", Token::While),
 	("fn", Token::Function),
+	("struct", Token::Struct),
+	("enum", Token::Enum),
 	("return", Token::Return),
+	("as", Token::As),
+	("true", Token::Literal(Literal::Boolean(true))),
+	("false", Token::Literal(Literal::Boolean(false))),
 ];
\ No newline at end of file
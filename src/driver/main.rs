@@ -1,13 +1,68 @@
-use compiler::cli;
+use std::process::ExitCode;
 
-fn main() {
+use compiler::cli::{self, Command, ErrorFormat};
+use compiler::error::diagnostic::{Diagnostic, Diagnostics, Note, Renderer, Severity, SourceMap};
+use compiler::interpreting::value::Value;
+
+fn main() -> ExitCode {
     let args: cli::Args = cli::parse_args();
 	if args.debug() {
 		dbg!(&args);
 	}
 
-	match cli::compile(&args) {
-		Err(error) => println!("Error: {}", error),
-		Ok(_) => println!("{}\nSuccessfully compiled files!", "=".repeat(28)),
+	let mut diagnostics = Diagnostics::new(args.error_limit());
+	let mut sources = SourceMap::new();
+	let mut returned = None;
+	match args.command() {
+		Some(Command::Run { file }) => returned = cli::run(&args, file, &mut diagnostics, &mut sources),
+		None => cli::compile(&args, &mut diagnostics, &mut sources),
+	}
+
+	match args.error_format() {
+		ErrorFormat::Human => print_human(&args, &diagnostics, &mut sources),
+		ErrorFormat::Json => {
+			for diagnostic in diagnostics.diagnostics() {
+				eprintln!("{}", diagnostic.to_json());
+			}
+		},
+	}
+
+	if diagnostics.has_errors() {
+		return ExitCode::FAILURE;
+	}
+
+	match (args.command(), returned) {
+		// Exit with what main returned, truncated the same way the shell does it
+		(Some(Command::Run { .. }), Some(Value::Integer(status, _))) => ExitCode::from(status as u8),
+		(Some(Command::Run { .. }), _) => ExitCode::SUCCESS,
+		(None, _) => {
+			println!("{}\nSuccessfully compiled files!", "=".repeat(28));
+			ExitCode::SUCCESS
+		},
+	}
+}
+
+// Render diagnostics followed by a summary of how many there were
+fn print_human(args: &cli::Args, diagnostics: &Diagnostics, sources: &mut SourceMap) {
+	let renderer = Renderer::new(args.use_color());
+	for diagnostic in diagnostics.diagnostics() {
+		eprint!("{}", renderer.render(diagnostic, sources));
+	}
+
+	let warning_count = diagnostics.warning_count();
+	if warning_count > 0 {
+		let summary = Diagnostic::new(Severity::Warning, format!("{warning_count} warning{} emitted", if warning_count == 1 { "" } else { "s" }), None, Vec::new());
+		eprint!("{}", renderer.render(&summary, sources));
+	}
+
+	if diagnostics.has_errors() {
+		let mut notes = Vec::new();
+		if diagnostics.suppressed_count() > 0 {
+			notes.push(Note::new(None, format!("{} more errors were not shown; raise --error-limit to see them", diagnostics.suppressed_count())));
+		}
+
+		let count = diagnostics.error_count();
+		let summary = Diagnostic::new(Severity::Error, format!("aborting due to {count} previous error{}", if count == 1 { "" } else { "s" }), None, notes);
+		eprint!("{}", renderer.render(&summary, sources));
 	}
 }
@@ -0,0 +1,566 @@
+// Small programs compiled with compile_source and run with the interpreter, so no LLVM toolchain is needed
+#![allow(clippy::result_large_err)]
+
+use compiler::{compile_source, CompileOptions};
+use compiler::error::{Error, Result};
+use compiler::generating::Generator;
+use compiler::generating::writer::Writer;
+use compiler::interpreting::Interpreter;
+use compiler::interpreting::value::Value;
+use compiler::parsing::Parser;
+use compiler::scanning::Scanner;
+
+fn compile(source: &str) -> Result<String> {
+	compile_source(source, &CompileOptions::default())
+}
+
+// Codes of the errors compiling source failed with
+fn compile_errors(source: &str) -> Vec<&'static str> {
+	match compile(source) {
+		Err(Error::CompileFailed { errors }) => errors.iter().map(Error::code).collect(),
+		Err(error) => panic!("compiling failed without collecting errors: {error}"),
+		Ok(_) => panic!("program compiled"),
+	}
+}
+
+// Parse and generate source, keeping the generator so its errors and warnings can be looked at
+fn generate(source: &str) -> Generator<Vec<u8>> {
+	let mut parser = Parser::new(Scanner::from_str(String::from("<source>"), source)).expect("source isn't empty");
+	let program = parser.parse_program();
+	let mut generator = Generator::new(Writer::in_memory(String::from("<source>")));
+	generator.generate(&program).unwrap();
+
+	generator
+}
+
+// Check that source compiles, then interpret it, giving what it printed and what main returned
+fn run(source: &str) -> (String, Result<Value>) {
+	if let Err(error) = compile(source) {
+		panic!("program doesn't compile: {error:?}");
+	}
+
+	let mut parser = Parser::new(Scanner::from_str(String::from("<source>"), source)).expect("source isn't empty");
+	let program = parser.parse_program();
+	let mut out = Vec::new();
+	let result = Interpreter::new(&mut out).run(&program);
+
+	(String::from_utf8(out).expect("output is valid UTF-8"), result)
+}
+
+// What source printed, failing if it didn't run to completion
+fn output(source: &str) -> String {
+	let (out, result) = run(source);
+	if let Err(error) = result {
+		panic!("program failed: {error}");
+	}
+
+	out
+}
+
+// Line and column an error is reported at
+fn location(error: &Error) -> (usize, usize) {
+	let span = error.span().unwrap_or_else(|| panic!("error has no location: {error}"));
+	(span.line(), span.column())
+}
+
+// Message of the only error compiling source failed with
+fn compile_error_message(source: &str) -> String {
+	match compile(source) {
+		Err(Error::CompileFailed { errors }) if errors.len() == 1 => errors[0].root().to_string(),
+		result => panic!("expected exactly one error, got {result:?}"),
+	}
+}
+
+#[test]
+fn locals_are_reported_at_their_name() {
+	let warnings = generate("fn main() -> int {\n\tlet x: int = 1;\n\treturn 0;\n}").take_warnings();
+	assert_eq!(warnings.len(), 1);
+	assert_eq!((warnings[0].span().line(), warnings[0].span().column()), (2, 6));
+
+	let errors = generate("fn main() -> int {\n\tlet y = 2;\n\tlet y = 3;\n\treturn y;\n}").take_errors();
+	assert!(matches!(errors[0].root(), Error::SymbolDeclared { .. }), "{errors:?}");
+	assert_eq!(location(&errors[0]), (3, 6));
+	let declared = errors[0].notes()[0].span().unwrap();
+	assert_eq!((declared.line(), declared.column()), (2, 6));
+}
+
+#[test]
+fn compile_source_gives_the_module_or_every_error() {
+	let llvm = compile("fn main() -> int { return 42; }").unwrap();
+	assert!(llvm.contains("define dso_local i64 @main()"), "{llvm}");
+	assert!(llvm.contains("ret i64 42"), "{llvm}");
+
+	assert_eq!(compile_errors("fn main() -> int { return x; }\nfn f() -> int { return y; }"), ["E0015", "E0015"]);
+}
+
+#[test]
+fn interpreted_programs_print_and_return() {
+	let source = "fn fib(n: int) -> int { if n < 2 { return n; } return fib(n - 1) + fib(n - 2); }
+fn main() -> int { let i = 0; while i < 6 { print fib(i); i = i + 1; } return 7; }";
+	assert_eq!(output(source), "0\n1\n1\n2\n3\n5\n");
+	assert_eq!(run(source).1.unwrap().as_integer().unwrap(), 7);
+}
+
+#[test]
+fn runtime_errors_are_located() {
+	let (_, result) = run("fn main() -> int {\n\tlet z = 0;\n\tprint 1 / z;\n\treturn 0;\n}");
+	let error = result.unwrap_err();
+	assert_eq!(error.code(), "E0031");
+	assert_eq!(location(&error), (3, 8));
+}
+
+#[test]
+fn block_comments_nest() {
+	let source = "/* outer /* inner */ still a comment */\nfn main() -> int {\n\t// line comment\n\tprint /* in an expression */ 1;\n\treturn 0;\n}";
+	assert_eq!(output(source), "1\n");
+
+	// Only the outer comment is left open
+	let Err(Error::CompileFailed { errors }) = compile("fn main() -> int { return 0; }\n/* outer /* inner */") else {
+		panic!("program compiled");
+	};
+	assert_eq!(errors.iter().map(Error::code).collect::<Vec<_>>(), ["E0035"]);
+	assert_eq!(location(&errors[0]), (2, 1));
+}
+
+#[test]
+fn integer_literals_can_be_written_in_other_bases() {
+	let source = "fn main() -> int { print 0xFF; print 0o17; print 0b1010; print 1_000_000; print 0x7FFF_FFFF_FFFF_FFFF; return 0; }";
+	assert_eq!(output(source), "255\n15\n10\n1000000\n9223372036854775807\n");
+	assert_eq!(compile_errors("fn main() -> int { print 0b102; return 0; }"), ["E0014"]);
+}
+
+#[test]
+fn boolean_literals_can_be_stored_and_tested() {
+	let source = "fn main() -> int { let found = true; let missing: bool = false; if found { print 1; } if missing { print 2; } if found == (1 < 2) { print 3; } return 0; }";
+	assert_eq!(output(source), "1\n3\n");
+	assert_eq!(compile_errors("fn main() -> int { let x: int = true; return x; }"), ["E0021"]);
+}
+
+#[test]
+fn prefix_operators() {
+	let source = "fn main() -> int { let x = 5; let found = false; print -x; print -(-3); print ~x; print ~0; if !found { print 1; } return 0; }";
+	assert_eq!(output(source), "-5\n3\n-6\n-1\n1\n");
+	assert_eq!(compile_errors("fn main() -> int { let b = -true; let c = !1; return 0; }"), ["E0036", "E0036"]);
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+	let source = "fn noisy(x: int) -> bool { print x; return x > 0; }
+fn main() -> int {
+	if noisy(0) && noisy(1) { print 10; }
+	if noisy(2) || noisy(3) { print 20; }
+	if noisy(4) && noisy(-5) { print 30; }
+	if noisy(-6) || noisy(7) { print 40; }
+	return 0;
+}";
+	assert_eq!(output(source), "0\n2\n20\n4\n-5\n-6\n7\n40\n");
+	assert!(compile(source).unwrap().contains("phi i1"));
+}
+
+#[test]
+fn bitwise_and_remainder_operators() {
+	let source = "fn main() -> int { let a = 6; let b = 3; print a & b; print a | b; print a ^ b; print 1 << a; print -16 >> 2; print 7 % b; print -7 % b; return 0; }";
+	assert_eq!(output(source), "2\n7\n5\n64\n-4\n1\n-1\n");
+	assert_eq!(compile_errors("fn main() -> int { let b = true & false; return 0; }"), ["E0019"]);
+}
+
+#[test]
+fn compound_assignment_evaluates_its_target_once() {
+	let source = "fn noisy(p: int*) -> int* { print 0; return p; }
+fn main() -> int {
+	let x = 5;
+	*noisy(&x) += 10;
+	print x;
+	x -= 3; x *= 2; x /= 4; x %= 4; x <<= 3; x |= 1; x ^= 3; x &= 14; x >>= 1;
+	print x;
+	print x++;
+	print ++x;
+	print x--;
+	print --x;
+	return 0;
+}";
+	assert_eq!(output(source), "0\n15\n1\n1\n3\n3\n1\n");
+	let llvm = compile(source).unwrap();
+	assert_eq!(llvm.matches("call i64* @noisy").count(), 1, "{llvm}");
+	assert_eq!(compile_errors("fn main() -> int { 1 += 2; return 0; }"), ["E0028"]);
+}
+
+#[test]
+fn sized_integer_literals_are_fitted() {
+	assert_eq!(output("fn main() -> int { let b: u8 = 255; let c: i8 = -128; print b; print c; return 0; }"), "255\n-128\n");
+	assert_eq!(compile_errors("fn main() -> int { let b: u8 = 256; return 0; }"), ["E0038"]);
+	assert_eq!(compile_errors("fn main() -> int { let c: i8 = -129; return 0; }"), ["E0038"]);
+}
+
+#[test]
+fn literals_can_use_the_whole_range_of_64_bit_formats() {
+	let source = r#"fn main() -> int {
+	let h: u64 = 0xcbf29ce484222325;
+	let top: u64 = 18446744073709551615;
+	let min: i64 = -9223372036854775808;
+	print h;
+	print top;
+	print min;
+	print top == 0xFFFFFFFFFFFFFFFF;
+	return 0;
+}"#;
+	assert_eq!(output(source), "14695981039346656037\n18446744073709551615\n-9223372036854775808\n1\n");
+	assert!(compile(source).unwrap().contains("store i64 14695981039346656037"));
+
+	assert_eq!(compile_errors("fn main() -> int { let x = 0xcbf29ce484222325; return 0; }"), ["E0038"]);
+	assert_eq!(compile_errors("fn main() -> int { let x: i64 = 9223372036854775808; return 0; }"), ["E0038"]);
+	assert_eq!(compile_errors("fn main() -> int { let x: u64 = -1; return 0; }"), ["E0038"]);
+	assert_eq!(compile_errors("fn main() -> int { print 18446744073709551616; return 0; }"), ["E0014"]);
+	assert_eq!(
+		compile_error_message("fn main() -> int { print 18446744073709551616; return 0; }"),
+		"StringParseError: Literal '18446744073709551616' doesn't fit in u64, the widest integer format",
+	);
+}
+
+#[test]
+fn literal_operands_take_the_other_operands_format() {
+	assert_eq!(output("fn main() -> int { let b: u8 = 250; b = b + 10; print b; return 0; }"), "4\n");
+	assert_eq!(compile_errors("fn main() -> int { let b: u8 = 250; b = b + 256; return 0; }"), ["E0038"]);
+	assert_eq!(compile_error_message("fn main() -> int { let x: u8 = 256; return 0; }"), "LiteralOutOfRange: 256 doesn't fit in u8");
+}
+
+#[test]
+fn literal_expressions_are_folded_as_the_format_they_are_used_as() {
+	let source = r#"fn top() -> u64 { return 1 << 63; }
+fn main() -> int {
+	let x: u8 = 1 + 1;
+	let z: u32 = 1 << 31;
+	let big: u64 = 0xFFFFFFFFFFFFFFFF >> 60;
+	print x;
+	print z;
+	print big;
+	print top();
+	x = 0xFF << 4;
+	print x;
+	return 0;
+}"#;
+	assert_eq!(output(source), "2\n2147483648\n15\n9223372036854775808\n240\n");
+	let llvm = compile(source).unwrap();
+	assert!(llvm.contains("store i32 2147483648"), "{llvm}");
+	assert!(llvm.contains("ret i64 9223372036854775808"), "{llvm}");
+
+	assert_eq!(compile_error_message("fn main() -> int { let x: u8 = 200 + 100; return 0; }"), "ConstantOverflow: 200 + 100 doesn't fit in u8");
+	assert_eq!(compile_error_message("fn main() -> int { let z: u32 = 1 << 32; return 0; }"), "ConstantOverflow: 1 << 32 doesn't fit in u32");
+	assert_eq!(compile_errors("fn main() -> int { let z: u32 = 1 << 32; return 0; }"), ["E0039"]);
+	assert_eq!(compile_errors("fn main() -> int { let q = 1 / 0; return 0; }"), ["E0031"]);
+}
+
+#[test]
+fn bitwise_not_of_literals_flips_the_bits_of_their_format() {
+	let source = r#"fn main() -> int {
+	let x: u8 = ~0;
+	let b: u8 = 0xAB;
+	let m: u64 = ~0;
+	let k: u16 = ~0x0F;
+	print x;
+	print b & ~0x0F;
+	print m;
+	print k;
+	print ~0;
+	return 0;
+}"#;
+	assert_eq!(output(source), "255\n160\n18446744073709551615\n65520\n-1\n");
+	let llvm = compile(source).unwrap();
+	assert!(llvm.contains("store i8 255"), "{llvm}");
+	assert!(llvm.lines().any(|line| line.contains("and i8 %") && line.ends_with(", 240")), "{llvm}");
+}
+
+#[test]
+fn casts_truncate_and_extend_integers() {
+	let source = r#"fn main() -> int {
+	let small: i8 = -2;
+	print 300 as u8;
+	print -1 as u16;
+	print small as u64;
+	print small as u8 as int;
+	print true as int;
+	print 5 as bool;
+	print 0xcbf29ce484222325 as u64;
+	return 0;
+}"#;
+	assert_eq!(output(source), "44\n65535\n18446744073709551614\n254\n1\n1\n14695981039346656037\n");
+	let llvm = compile(source).unwrap();
+	assert!(llvm.contains("sext i8"), "{llvm}");
+	assert!(llvm.contains("trunc i64 300 to i8"), "{llvm}");
+	assert_eq!(compile_errors("fn main() -> int { let p = true as int*; return 0; }"), ["E0026"]);
+}
+
+#[test]
+fn null_pointers_from_casts_are_caught_when_dereferenced() {
+	let source = "fn main() -> int {\n\tlet p = 0 as int*;\n\tprint *p;\n\treturn 0;\n}";
+	let error = run(source).1.unwrap_err();
+	assert_eq!(error.code(), "E0032");
+	assert_eq!(location(&error), (3, 8));
+}
+
+#[test]
+fn float_literal_expressions_are_folded_as_the_format_they_are_used_as() {
+	let source = "fn main() -> int { let f: f32 = 1.0 / 3.0; let d = 1.0 / 3.0; print f; print d; print f == 1.0 / 3.0; return 0; }";
+	assert_eq!(output(source), "0.333333\n0.333333\n1\n");
+	let llvm = compile(source).unwrap();
+	assert!(llvm.contains("store float 0x3FD5555560000000"), "{llvm}");
+	assert!(llvm.contains("store double 0x3FD5555555555555"), "{llvm}");
+
+	assert_eq!(compile_errors("fn main() -> int { let f = 1.0 % 2.0; return 0; }"), ["E0019"]);
+	assert_eq!(compile_errors("fn main() -> int { let f: f32 = 1 + 2.0; return 0; }"), ["E0037"]);
+	assert_eq!(compile_errors("fn main() -> int { let f = 1.5e; return 0; }"), ["E0040"]);
+	assert_eq!(output("fn main() -> int { let f: f32 = -2.7; print f as int; print 3 as f32; return 0; }"), "-2\n3.000000\n");
+}
+
+#[test]
+fn char_literals_decode_their_escapes() {
+	let source = r#"fn main() -> int {
+	let c = 'a';
+	let n: char = '\n';
+	print c;
+	print '\x41';
+	print '\'';
+	print n as int;
+	print '\\' as u8;
+	return 0;
+}"#;
+	assert_eq!(output(source), "a\nA\n'\n10\n92\n");
+}
+
+#[test]
+fn invalid_char_literals_are_reported() {
+	let escape = compile_error_message("fn main() -> int { let c = '\\q'; return 0; }");
+	assert!(escape.starts_with("InvalidEscape:") && escape.contains("\\q"), "{escape}");
+	assert_eq!(compile_errors("fn main() -> int { let c = '\\x4'; return 0; }"), ["E0041"]);
+	assert_eq!(compile_errors("fn main() -> int { let c = '\\xZZ'; return 0; }"), ["E0041"]);
+	assert_eq!(compile_errors("fn main() -> int { let c = 'ab'; return 0; }"), ["E0042"]);
+	assert_eq!(compile_errors("fn main() -> int { let c = ''; return 0; }"), ["E0042"]);
+
+	let errors = match compile("fn main() -> int {\n\tlet c = 'a;\n\treturn 0;\n}") {
+		Err(Error::CompileFailed { errors }) => errors,
+		result => panic!("expected the literal to be reported, got {result:?}"),
+	};
+	assert_eq!(errors[0].code(), "E0043");
+	assert_eq!(location(&errors[0]), (2, 10));
+}
+
+#[test]
+fn string_literals_are_shared_constants() {
+	let source = r#"fn main() -> int {
+	let s = "hi\tthere";
+	let t = "hi\tthere";
+	print s;
+	print *s;
+	return 0;
+}"#;
+	assert_eq!(output(source), "hi\tthere\nh\n");
+	let llvm = compile(source).unwrap();
+	assert!(llvm.contains(r#"@str.0 = private unnamed_addr constant [9 x i8] c"hi\09there\00""#), "{llvm}");
+	assert!(!llvm.contains("@str.1"), "{llvm}");
+
+	let error = run("fn main() -> int {\n\tlet s = \"hi\";\n\t*s = 'x';\n\treturn 0;\n}").1.unwrap_err();
+	assert_eq!(error.code(), "E0044");
+	assert_eq!(location(&error), (3, 2));
+	assert_eq!(compile_errors("fn main() -> int { let s = \"a\\qb\"; return 0; }"), ["E0041"]);
+}
+
+#[test]
+fn formatted_print_uses_a_conversion_per_type() {
+	let llvm = compile("fn main() -> int { let x = 42; let b: u8 = 200; let small: i8 = -5; println \"{} {} {}\", x, b, small; return 0; }").unwrap();
+	assert!(llvm.contains(r#"c"%lld %llu %lld\0A\00""#), "{llvm}");
+}
+
+#[test]
+fn formatted_print_output() {
+	let source = r#"fn main() -> int {
+	let b: u8 = 200;
+	let f: f32 = 1.5;
+	let top: u64 = 18446744073709551615;
+	println "b = {}, f = {}, c = {}, s = {}", b, f, 'z', "str";
+	println "{} {} {}", top, top == 0xFFFFFFFFFFFFFFFF, 0xcbf29ce484222325 as u64;
+	print "{{}} 100% ";
+	println "{}", -7;
+	return 0;
+}"#;
+	assert_eq!(output(source), "b = 200, f = 1.500000, c = z, s = str\n18446744073709551615 true 14695981039346656037\n{} 100% -7\n");
+	assert_eq!(compile_errors("fn main() -> int { println \"{} {}\", 1; return 0; }"), ["E0046"]);
+	assert_eq!(compile_errors("fn main() -> int { println \"{\", 1; return 0; }"), ["E0045"]);
+	assert_eq!(compile_errors("fn main() -> int { println \"{}\", 18446744073709551615; return 0; }"), ["E0038"]);
+}
+
+#[test]
+fn arrays_can_be_indexed_and_assigned() {
+	let source = r#"fn main() -> int {
+	let a = [1, 2, 3];
+	let z: [u8; 4] = [7; 4];
+	let i = 2;
+	a[i] = a[0] + a[1];
+	z[1] += 250;
+	println "{} {} {} {}", a[2], z[0], z[1], z[3];
+	return 0;
+}"#;
+	assert_eq!(output(source), "3 7 1 7\n");
+
+	assert_eq!(compile_errors("fn main() -> int { let x = 1; print x[0]; return 0; }"), ["E0047"]);
+	assert_eq!(compile_errors("fn main() -> int { let a = [1, 2]; print a[true]; return 0; }"), ["E0048"]);
+	assert_eq!(compile_errors("fn main() -> int { let a = [1, 2]; print a; return 0; }"), ["E0049"]);
+	assert_eq!(compile_errors("fn main() -> int { let a: [int; x] = [1]; return 0; }"), ["E0050"]);
+}
+
+#[test]
+fn array_literal_elements_are_used_as_the_declared_element_format() {
+	let source = r#"fn main() -> int {
+	let a: [u64; 2] = [18446744073709551615, 1 << 63];
+	let b: [u64; 3] = [0xcbf29ce484222325; 3];
+	let c: [u8; 2] = [200 + 55, ~0];
+	println "{} {} {} {} {}", a[0], a[1], b[2], c[0], c[1];
+	return 0;
+}"#;
+	assert_eq!(output(source), "18446744073709551615 9223372036854775808 14695981039346656037 255 255\n");
+	let llvm = compile(source).unwrap();
+	assert!(llvm.contains("[2 x i64] [i64 18446744073709551615, i64 9223372036854775808]"), "{llvm}");
+
+	assert_eq!(compile_errors("fn main() -> int { let a: [u8; 1] = [256]; return 0; }"), ["E0038"]);
+	assert_eq!(compile_errors("fn main() -> int { let a = [18446744073709551615]; return 0; }"), ["E0038"]);
+}
+
+#[test]
+fn bounds_checks_are_only_emitted_when_enabled() {
+	let source = "fn main() -> int { let a = [1, 2, 3]; let i = 1; print a[i]; return 0; }";
+	let checked = compile_source(source, &CompileOptions::default().with_bounds_checks(true)).unwrap();
+	assert!(checked.contains("<source>:1:58: index out of bounds: the len is 3 but the index is %lld"), "{checked}");
+	assert!(checked.contains("call void @abort()"), "{checked}");
+
+	let unchecked = compile(source).unwrap();
+	assert!(!unchecked.contains("index out of bounds"), "{unchecked}");
+}
+
+#[test]
+fn constant_indexes_are_checked_when_compiling() {
+	assert_eq!(compile_errors("fn main() -> int { let a = [1, 2, 3]; print a[3]; return 0; }"), ["E0051"]);
+	assert_eq!(compile_errors("fn main() -> int { let a = [1, 2, 3]; print a[-1]; return 0; }"), ["E0051"]);
+	assert_eq!(
+		compile_error_message("fn main() -> int { let a = [1, 2, 3]; print a[5]; return 0; }"),
+		"IndexOutOfBounds: The len is 3 but the index is 5",
+	);
+}
+
+#[test]
+fn indexes_are_checked_when_interpreting() {
+	let (out, result) = run("fn main() -> int { let a = [1, 2, 3]; let i = 0; while i < 4 { print a[i]; i++; } return 0; }");
+	assert_eq!(out, "1\n2\n3\n");
+	assert_eq!(result.unwrap_err().code(), "E0051");
+}
+
+#[test]
+fn out_of_bounds_indexes_are_reported_at_the_index() {
+	let source = "fn main() -> int {\n\tlet a = [1, 2];\n\tlet i = 5;\n\tprint a[i];\n\treturn 0;\n}";
+	let (_, result) = run(source);
+	let error = result.unwrap_err();
+	assert_eq!(error.code(), "E0051");
+	assert_eq!(location(&error), (4, 10));
+
+	// The same location the compiled check reports
+	let checked = compile_source(source, &CompileOptions::default().with_bounds_checks(true)).unwrap();
+	assert!(checked.contains("<source>:4:10: index out of bounds"), "{checked}");
+}
+
+#[test]
+fn struct_literals_and_field_access() {
+	let source = r#"struct Line { from: Point, to: Point, width: u8 }
+struct Point { x: int, y: int }
+fn length(line: Line) -> int { return line.to.x - line.from.x + line.to.y - line.from.y; }
+fn main() -> int {
+	let line = Line { width: 200 + 55, to: Point { y: 7, x: 5 }, from: Point { x: 1, y: 2 } };
+	line.from.x = 0;
+	line.to.y += 1;
+	println "{} {} {}", line.width, line.to.y, length(line);
+	return 0;
+}"#;
+	assert_eq!(output(source), "255 8 11\n");
+}
+
+#[test]
+fn fields_are_reached_through_pointers() {
+	let source = r#"struct Counter { count: int, step: int }
+fn bump(counter: Counter*) { counter.count += counter.step; }
+fn main() -> int {
+	let c = Counter { count: 0, step: 3 };
+	let p = &c;
+	bump(p);
+	bump(&c);
+	p.step = 10;
+	bump(p);
+	println "{} {}", c.count, (*p).step;
+	return 0;
+}"#;
+	assert_eq!(output(source), "16 10\n");
+}
+
+#[test]
+fn invalid_struct_literals_and_fields_are_reported() {
+	let declared = "struct P { x: int, y: int }\n";
+	let check = |body: &str| compile_errors(&format!("{declared}fn main() -> int {{ {body} return 0; }}"));
+	assert_eq!(check("let p = P { x: 1, z: 2, y: 3 };"), ["E0053"]);
+	assert_eq!(check("let p = P { x: 1, x: 2, y: 3 };"), ["E0052"]);
+	assert_eq!(check("let p = P { x: 1 };"), ["E0054"]);
+	assert_eq!(check("let p = P { x: 1, y: 2 }; print p.z;"), ["E0053"]);
+	assert_eq!(check("let n = 1; print n.x;"), ["E0055"]);
+	assert_eq!(check("let p = P { x: 1, y: 2 }; print p;"), ["E0049"]);
+	assert_eq!(
+		compile_error_message(&format!("{declared}fn main() -> int {{ let p = P {{ y: 1 }}; return 0; }}")),
+		"MissingFields: P literal is missing x",
+	);
+}
+
+#[test]
+fn recursive_structs_are_reported() {
+	assert_eq!(compile_errors("struct Node { value: int, next: Node }\nfn main() -> int { return 0; }"), ["E0056"]);
+	assert_eq!(compile_errors("struct A { b: B }\nstruct B { a: A }\nfn main() -> int { return 0; }"), ["E0056", "E0056"]);
+	// Through a pointer the size is known
+	assert_eq!(output("struct Node { value: int, next: Node* }\nfn main() -> int { let n = Node { value: 1, next: 0 as Node* }; print n.value; return 0; }"), "1\n");
+}
+
+#[test]
+fn enum_variants_are_numbered_after_the_one_before() {
+	let source = r#"enum Color { Red, Green = 5, Blue, Black = -2, White }
+fn main() -> int {
+	println "{} {} {} {} {}", Color::Red, Color::Green, Color::Blue, Color::Black, Color::White;
+	return 0;
+}"#;
+	assert_eq!(output(source), "0 5 6 -2 -1\n");
+}
+
+#[test]
+fn enums_can_only_be_compared_for_equality() {
+	let source = r#"enum Color { Red, Green = 5, Blue }
+fn is_green(color: Color) -> bool { return color == Color::Green; }
+fn main() -> int {
+	let c = Color::Blue;
+	println "{} {} {} {}", is_green(c), is_green(Color::Green), c != Color::Red, c == Color::Blue;
+	return 0;
+}"#;
+	assert_eq!(output(source), "false true true true\n");
+
+	let declared = "enum Color { Red, Green }\n";
+	let check = |body: &str| compile_errors(&format!("{declared}fn main() -> int {{ {body} return 0; }}"));
+	assert_eq!(check("let c = Color::Red + 1;"), ["E0019"]);
+	assert_eq!(check("let c = Color::Red + Color::Green;"), ["E0019"]);
+	assert_eq!(check("let b = Color::Red < Color::Green;"), ["E0020"]);
+	assert_eq!(check("let b = Color::Red == 0;"), ["E0020"]);
+	assert_eq!(check("let c = Color::Purple;"), ["E0058"]);
+}
+
+#[test]
+fn invalid_enums_are_reported() {
+	assert_eq!(compile_errors("enum E { A, B, A }\nfn main() -> int { return 0; }"), ["E0057"]);
+	assert_eq!(compile_errors("enum E { A = 9223372036854775807, B }\nfn main() -> int { return 0; }"), ["E0059"]);
+	assert_eq!(
+		compile_error_message("enum E { A = 9223372036854775807, B }\nfn main() -> int { return 0; }"),
+		"DiscriminantOverflow: Discriminant 9223372036854775808 of E::B doesn't fit in int, which enums are stored as",
+	);
+	assert_eq!(
+		compile_error_message("enum E { A = 0xFFFFFFFFFFFFFFFF }\nfn main() -> int { return 0; }"),
+		"DiscriminantOverflow: Discriminant 18446744073709551615 of E::A doesn't fit in int, which enums are stored as",
+	);
+}